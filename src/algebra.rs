@@ -1,14 +1,50 @@
 use std::cmp;
-use std::ops::Add;
+use std::fmt;
+use std::ops::{Add, Mul};
 
-#[derive(Clone, Debug)]
+use crate::packed::Coefficients;
+
+/// A Laurent polynomial in v over F_p, stored as its non-zero terms in
+/// increasing order of their powers, which may be negative.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Polynomial {
-    pub powers: Vec<u32>,
-    pub coefs: Vec<u8>,
-    pub p: u8,
+    pub powers: Vec<i32>,
+    pub coefs: Vec<u32>,
+    pub p: u32,
+}
+
+/// Inverse of a non-zero `x` modulo the prime `p`.
+pub fn inverse_mod(x: u32, p: u32) -> u32 {
+    let p = p as u64;
+    let mut res: u64 = 1;
+    let mut base = x as u64 % p;
+    let mut exp = p - 2;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = res * base % p;
+        }
+        base = base * base % p;
+        exp >>= 1;
+    }
+    res as u32
 }
 
-fn add(a_keys: &Vec<u32>, a_values: &Vec<u8>, b_keys: &Vec<u32>, b_values: &Vec<u8>, p: u8) -> (Vec<u32>, Vec<u8>) {
+/// Whether `p` is prime, by trial division.
+pub fn is_prime(p: u32) -> bool {
+    if p < 2 {
+        return false;
+    }
+    let mut d: u32 = 2;
+    while (d as u64) * (d as u64) <= p as u64 {
+        if p.is_multiple_of(d) {
+            return false;
+        }
+        d += 1;
+    }
+    true
+}
+
+fn add(a_keys: &[i32], a_values: &[u32], b_keys: &[i32], b_values: &[u32], p: u32) -> (Vec<i32>, Vec<u32>) {
     let mut i = 0;
     let mut j = 0;
     let mut c_keys = Vec::new();
@@ -24,7 +60,7 @@ fn add(a_keys: &Vec<u32>, a_values: &Vec<u8>, b_keys: &Vec<u32>, b_values: &Vec<
             c_values.push(b_values[j]);
             j += 1;
         } else {
-            let new_val = (a_values[i] + b_values[j]) % p;
+            let new_val = ((a_values[i] as u64 + b_values[j] as u64) % p as u64) as u32;
             if new_val != 0 {
                 c_keys.push(a_keys[i]);
                 c_values.push(new_val);
@@ -65,10 +101,139 @@ impl Add for Polynomial {
     }
 }
 
+/// Below this many terms in either factor, polynomials are multiplied term
+/// by term.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Product of two dense coefficient vectors, with coefficients reduced mod p.
+fn schoolbook(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    let mut res: Vec<u64> = vec![0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        if *x == 0 {
+            continue;
+        }
+        for (j, y) in b.iter().enumerate() {
+            res[i + j] = (res[i + j] + x * y % p) % p;
+        }
+    }
+    res
+}
+
+fn add_dense(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    let mut res = a.to_vec();
+    res.resize(cmp::max(a.len(), b.len()), 0);
+    for (i, y) in b.iter().enumerate() {
+        res[i] = (res[i] + y) % p;
+    }
+    res
+}
+
+/// Karatsuba multiplication of two dense coefficient vectors of equal length.
+fn karatsuba(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    let n = a.len();
+    if n < KARATSUBA_THRESHOLD {
+        return schoolbook(a, b, p);
+    }
+    let m = n / 2;
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+    let z0 = karatsuba(a0, b0, p);
+    let z2 = karatsuba(a1, b1, p);
+    let z1 = karatsuba(&add_dense(a1, a0, p), &add_dense(b1, b0, p), p);
+
+    let mut res = vec![0; 2 * n - 1];
+    for (i, x) in z0.iter().enumerate() {
+        res[i] = (res[i] + x) % p;
+        res[i + m] = (res[i + m] + p - x) % p;
+    }
+    for (i, x) in z2.iter().enumerate() {
+        res[i + 2 * m] = (res[i + 2 * m] + x) % p;
+        res[i + m] = (res[i + m] + p - x) % p;
+    }
+    for (i, x) in z1.iter().enumerate() {
+        res[i + m] = (res[i + m] + x) % p;
+    }
+    res
+}
+
+/// Product of two polynomials given by their terms, for inputs too short or
+/// too sparse for Karatsuba to pay off.
+fn mul_sparse(a: &Polynomial, b: &Polynomial) -> Polynomial {
+    let p = a.p as u64;
+    let shift = a.min_power() + b.min_power();
+    let span = (a.max_power() + b.max_power() - shift) as usize + 1;
+    if span <= a.powers.len() * b.powers.len() {
+        let mut res: Vec<u64> = vec![0; span];
+        for (x_power, x) in a.powers.iter().zip(&a.coefs) {
+            for (y_power, y) in b.powers.iter().zip(&b.coefs) {
+                let i = (x_power + y_power - shift) as usize;
+                res[i] = (res[i] + (*x as u64) * (*y as u64) % p) % p;
+            }
+        }
+        return Polynomial::from_dense(&res, shift, a.p);
+    }
+    let mut terms: Vec<(i32, u64)> = Vec::with_capacity(a.powers.len() * b.powers.len());
+    for (x_power, x) in a.powers.iter().zip(&a.coefs) {
+        for (y_power, y) in b.powers.iter().zip(&b.coefs) {
+            terms.push((x_power + y_power, (*x as u64) * (*y as u64) % p));
+        }
+    }
+    terms.sort_unstable_by_key(|x| x.0);
+    let mut powers = Vec::new();
+    let mut coefs = Vec::new();
+    for (power, coef) in terms {
+        if powers.last() == Some(&power) {
+            let last = coefs.last_mut().unwrap();
+            *last = ((*last as u64 + coef) % p) as u32;
+        } else {
+            powers.push(power);
+            coefs.push(coef as u32);
+        }
+    }
+    let (powers, coefs) = powers.into_iter().zip(coefs).filter(|x| x.1 != 0).unzip();
+    Polynomial { powers, coefs, p: a.p }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: &Polynomial) -> Polynomial {
+        if self.is_zero() || rhs.is_zero() {
+            return Polynomial::zero(self.p);
+        }
+        // Decide on the spans alone, so that sparse polynomials of a large
+        // span are never made dense.
+        let span = |x: &Polynomial| (x.max_power() as i64 - x.min_power() as i64) as usize + 1;
+        let terms = self.powers.len() + rhs.powers.len();
+        if cmp::min(self.powers.len(), rhs.powers.len()) < KARATSUBA_THRESHOLD
+            || span(self) + span(rhs) > 2 * terms
+        {
+            return mul_sparse(self, rhs);
+        }
+        let a = self.to_dense();
+        let b = rhs.to_dense();
+        let len = a.len() + b.len() - 1;
+        let n = cmp::max(a.len(), b.len());
+        let (mut a, mut b) = (a, b);
+        a.resize(n, 0);
+        b.resize(n, 0);
+        let mut dense = karatsuba(&a, &b, self.p as u64);
+        dense.truncate(len);
+        Polynomial::from_dense(&dense, self.min_power() + rhs.min_power(), self.p)
+    }
+}
+impl Mul for Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Polynomial) -> Polynomial {
+        &self * &rhs
+    }
+}
+
 impl Polynomial {
-    pub fn new(elements: Vec<(u32, u8)>, p: u8) -> Polynomial {
-        let mut powers: Vec<u32> = Vec::new();
-        let mut coefs: Vec<u8> = Vec::new();
+    pub fn new(elements: Vec<(i32, u32)>, p: u32) -> Polynomial {
+        let mut powers: Vec<i32> = Vec::new();
+        let mut coefs: Vec<u32> = Vec::new();
         for (a, b) in elements {
             powers.push(a);
             coefs.push(b);
@@ -76,11 +241,11 @@ impl Polynomial {
         Polynomial { powers, coefs, p }
     }
 
-    pub fn zero(p: u8) -> Polynomial {
+    pub fn zero(p: u32) -> Polynomial {
         Self::new(vec![], p)
     }
 
-    pub fn one(p: u8) -> Polynomial {
+    pub fn one(p: u32) -> Polynomial {
         Self::new(vec![(0, 1)], p)
     }
 
@@ -88,16 +253,40 @@ impl Polynomial {
         self.powers.is_empty()
     }
 
-    pub fn max_power(&self) -> u32 {
+    pub fn max_power(&self) -> i32 {
         *self.powers.last().unwrap()
     }
 
-    pub fn min_power(&self) -> u32 {
+    pub fn min_power(&self) -> i32 {
         *self.powers.first().unwrap()
     }
 
-    pub fn mult(&self, power: u32, neg: bool) -> Polynomial {
-        let new_powers: Vec<u32> = if self.is_zero() {
+    /// Coefficients of all powers from the lowest to the highest one. The
+    /// polynomial must be non-zero.
+    fn to_dense(&self) -> Vec<u64> {
+        let min_power = self.min_power();
+        let mut res = vec![0; (self.max_power() - min_power) as usize + 1];
+        for (power, coef) in self.powers.iter().zip(&self.coefs) {
+            res[(power - min_power) as usize] = *coef as u64;
+        }
+        res
+    }
+
+    /// Inverse of `to_dense`; `coefs` must already be reduced mod p.
+    fn from_dense(coefs: &[u64], shift: i32, p: u32) -> Polynomial {
+        let mut res = Self::zero(p);
+        for (i, coef) in coefs.iter().enumerate() {
+            if *coef != 0 {
+                res.powers.push(shift + i as i32);
+                res.coefs.push(*coef as u32);
+            }
+        }
+        res
+    }
+
+    /// Multiplies by ±v^power.
+    pub fn mult(&self, power: i32, neg: bool) -> Polynomial {
+        let new_powers: Vec<i32> = if self.is_zero() {
             Vec::new()
         } else if power == 0 {
             self.powers.clone()
@@ -115,61 +304,393 @@ impl Polynomial {
             p: self.p,
         }
     }
+
+    /// Divides by v^power and multiplies by the constant `c`.
+    pub fn shift_down_and_scale(&self, power: i32, c: u32) -> Polynomial {
+        let p = self.p as u64;
+        Polynomial {
+            powers: self.powers.iter().map(|x| x - power).collect(),
+            coefs: self.coefs.iter().map(|x| (*x as u64 * c as u64 % p) as u32).collect(),
+            p: self.p,
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+/// Prints the polynomial in increasing powers of v, as in `1 + 4v + v^3`.
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        for (i, (power, coef)) in self.powers.iter().zip(&self.coefs).enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            match (*power, *coef) {
+                (0, c) => write!(f, "{}", c)?,
+                (1, 1) => write!(f, "v")?,
+                (1, c) => write!(f, "{}v", c)?,
+                (k, 1) => write!(f, "v^{}", k)?,
+                (k, c) => write!(f, "{}v^{}", c, k)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A square matrix of polynomials, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Matrix {
-    pub d: [Polynomial; 9],
-    pub p: u8,
+    pub d: Vec<Polynomial>,
+    pub dim: usize,
+    pub p: u32,
 }
 
 impl Matrix {
-    pub fn zero(p: u8) -> Matrix {
-        let d: [Polynomial; 9] = [
-            Polynomial::zero(p),
-            Polynomial::zero(p),
-            Polynomial::zero(p),
-            Polynomial::zero(p),
-            Polynomial::zero(p),
-            Polynomial::zero(p),
-            Polynomial::zero(p),
-            Polynomial::zero(p),
-            Polynomial::zero(p),
-        ];
-        Matrix { d, p }
-    }
-
-    pub fn identity(p: u8) -> Matrix {
-        let mut res = Self::zero(p);
-        res.d[0] = Polynomial::one(p);
-        res.d[4] = Polynomial::one(p);
-        res.d[8] = Polynomial::one(p);
+    pub fn zero(dim: usize, p: u32) -> Matrix {
+        Matrix {
+            d: vec![Polynomial::zero(p); dim * dim],
+            dim,
+            p,
+        }
+    }
+
+    pub fn identity(dim: usize, p: u32) -> Matrix {
+        let mut res = Self::zero(dim, p);
+        for i in 0..dim {
+            res.d[i * dim + i] = Polynomial::one(p);
+        }
         res
     }
 
+    pub fn get(&self, row: usize, col: usize) -> &Polynomial {
+        &self.d[row * self.dim + col]
+    }
+
     pub fn projlen(&self) -> u32 {
-        let mut min_power: u32 = u32::MAX;
-        let mut max_power: u32 = u32::MIN;
-        for i in 0..9 {
-            if !&self.d[i].is_zero() {
-                min_power = cmp::min(min_power, (&self.d[i]).min_power());
-                max_power = cmp::max(max_power, (&self.d[i]).max_power());
+        let mut min_power: i32 = i32::MAX;
+        let mut max_power: i32 = i32::MIN;
+        for poly in &self.d {
+            if !poly.is_zero() {
+                min_power = cmp::min(min_power, poly.min_power());
+                max_power = cmp::max(max_power, poly.max_power());
+            }
+        }
+        (max_power - min_power) as u32 + 1
+    }
+
+    /// The representative of the projective class of the matrix, i.e. of the
+    /// matrix up to multiplication by c·v^k, whose lowest power is 0 and
+    /// whose first non-zero entry has lowest coefficient 1.
+    pub fn canonical(&self) -> Matrix {
+        let Some(first) = self.d.iter().find(|x| !x.is_zero()) else {
+            return self.clone();
+        };
+        let min_power = self
+            .d
+            .iter()
+            .filter(|x| !x.is_zero())
+            .map(|x| x.min_power())
+            .min()
+            .unwrap();
+        let c = inverse_mod(first.coefs[0], self.p);
+        Matrix {
+            d: self.d.iter().map(|x| x.shift_down_and_scale(min_power, c)).collect(),
+            dim: self.dim,
+            p: self.p,
+        }
+    }
+
+    /// Whether the matrix is c·v^k·I for some non-zero c and some k.
+    pub fn is_scalar(&self) -> bool {
+        let diagonal = self.get(0, 0);
+        if diagonal.powers.len() != 1 {
+            return false;
+        }
+        for row in 0..self.dim {
+            for col in 0..self.dim {
+                let entry = self.get(row, col);
+                if row == col && entry != diagonal || row != col && !entry.is_zero() {
+                    return false;
+                }
             }
         }
-        return max_power - min_power + 1;
+        true
+    }
+}
+
+/// The lowest and highest power of v occurring in a matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub min_power: i32,
+    pub max_power: i32,
+}
+
+impl Span {
+    pub fn projlen(&self) -> u32 {
+        (self.max_power - self.min_power + 1) as u32
+    }
+}
+
+/// A square matrix of Laurent polynomials over F_p in which all entries share
+/// one window of powers, v^base to v^(base + width - 1), with the
+/// coefficients of all entries stored densely in one buffer, packed into bits
+/// for p = 2 and p = 3. The window is kept no wider than the non-zero
+/// coefficients need, so its width is the projlen. This is the
+/// representation used by the searches, where projlen stays small and
+/// `Matrix` would allocate for every entry.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DenseMatrix {
+    pub coefs: Coefficients,
+    pub base: i32,
+    pub width: usize,
+    pub dim: usize,
+    pub p: u32,
+}
+
+impl DenseMatrix {
+    pub fn zero(dim: usize, p: u32) -> DenseMatrix {
+        DenseMatrix {
+            coefs: Coefficients::new(p),
+            base: 0,
+            width: 0,
+            dim,
+            p,
+        }
+    }
+
+    pub fn identity(dim: usize, p: u32) -> DenseMatrix {
+        let mut res = DenseMatrix::zero(dim, p);
+        res.reset(0, 1);
+        for i in 0..dim {
+            res.coefs.set(res.stride(), i * dim + i, 0, 1);
+        }
+        res
+    }
+
+    /// Values or words of `coefs` taken by every entry.
+    pub fn stride(&self) -> usize {
+        self.coefs.stride(self.width)
+    }
+
+    /// Makes the matrix zero with the window of `width` powers from
+    /// v^base, reusing the buffer.
+    pub fn reset(&mut self, base: i32, width: usize) {
+        self.base = base;
+        self.width = width;
+        let stride = self.stride();
+        self.coefs.reset(self.dim * self.dim, stride);
+    }
+
+    /// The coefficient of v^(base + k) in the entry.
+    pub fn coef(&self, row: usize, col: usize, k: usize) -> u32 {
+        self.coefs.get(self.stride(), row * self.dim + col, k)
+    }
+
+    /// Adds ±v^(offset + other.base - base) times entry (row, src) of `other`
+    /// to entry (row, dst), which must fit in the window.
+    pub fn add_shifted(&mut self, row: usize, dst: usize, other: &DenseMatrix, src: usize, offset: usize, neg: bool) {
+        let stride = self.stride();
+        let i = row * self.dim + dst;
+        let src_i = row * other.dim + src;
+        self.coefs.add_shifted(stride, i, &other.coefs, other.stride(), src_i, offset, neg, self.p);
+    }
+
+    pub fn projlen(&self) -> u32 {
+        self.width as u32
+    }
+
+    /// The window of powers, which has no zero powers at either end once the
+    /// matrix is trimmed.
+    pub fn span(&self) -> Span {
+        Span {
+            min_power: self.base,
+            max_power: self.base + self.width as i32 - 1,
+        }
+    }
+
+    /// Narrows the window to the powers with a non-zero coefficient in some
+    /// entry, and returns it.
+    pub fn trim(&mut self) -> Span {
+        let entries = self.dim * self.dim;
+        let Some((low, high)) = self.coefs.support(entries, self.stride()) else {
+            self.reset(0, 0);
+            return self.span();
+        };
+        let width = high - low + 1;
+        if width != self.width {
+            self.coefs.narrow(entries, self.width, width, low);
+            self.base += low as i32;
+            self.width = width;
+        }
+        self.span()
+    }
+
+    /// The representative of the projective class of the matrix, normalized
+    /// as in `Matrix::canonical`.
+    pub fn canonical(&self) -> DenseMatrix {
+        let entries = 0..self.dim * self.dim;
+        let stride = self.stride();
+        let first = entries
+            .flat_map(|i| (0..self.width).map(move |k| (i, k)))
+            .map(|(i, k)| self.coefs.get(stride, i, k))
+            .find(|x| *x != 0);
+        let mut res = DenseMatrix { base: 0, ..self.clone() };
+        if let Some(first) = first {
+            res.coefs.scale(inverse_mod(first, self.p), self.p);
+        }
+        res
+    }
+
+    /// Whether the matrix is c·v^k·I for some non-zero c and some k.
+    pub fn is_scalar(&self) -> bool {
+        let entry = |row: usize, col: usize| (0..self.width).map(move |k| self.coef(row, col, k));
+        if entry(0, 0).filter(|x| *x != 0).count() != 1 {
+            return false;
+        }
+        for row in 0..self.dim {
+            for col in 0..self.dim {
+                if row == col && !entry(row, col).eq(entry(0, 0)) || row != col && entry(row, col).any(|x| x != 0) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl From<&Matrix> for DenseMatrix {
+    fn from(mat: &Matrix) -> DenseMatrix {
+        let mut res = DenseMatrix::zero(mat.dim, mat.p);
+        let nonzero = || mat.d.iter().filter(|x| !x.is_zero());
+        let (Some(base), Some(top)) = (nonzero().map(|x| x.min_power()).min(), nonzero().map(|x| x.max_power()).max()) else {
+            return res;
+        };
+        res.reset(base, (top - base) as usize + 1);
+        let stride = res.stride();
+        for (i, entry) in mat.d.iter().enumerate() {
+            for (power, coef) in entry.powers.iter().zip(&entry.coefs) {
+                res.coefs.set(stride, i, (power - base) as usize, *coef);
+            }
+        }
+        res
+    }
+}
+
+impl From<&DenseMatrix> for Matrix {
+    fn from(mat: &DenseMatrix) -> Matrix {
+        let mut res = Matrix::zero(mat.dim, mat.p);
+        let stride = mat.stride();
+        for (i, entry) in res.d.iter_mut().enumerate() {
+            let terms = (0..mat.width)
+                .map(|k| (mat.base + k as i32, mat.coefs.get(stride, i, k)))
+                .filter(|x| x.1 != 0)
+                .collect();
+            *entry = Polynomial::new(terms, mat.p);
+        }
+        res
+    }
+}
+
+/// A square matrix of Laurent polynomials in v, over F_p or over the integers,
+/// on which the simple elements act by column operations.
+pub trait LaurentMatrix {
+    fn dim(&self) -> usize;
+
+    /// The zero matrix of the same dimension and over the same coefficients.
+    fn zero_like(&self) -> Self;
+
+    /// Adds ±v^power times column `src` of `other` to column `dst`.
+    fn add_column(&mut self, dst: usize, other: &Self, src: usize, power: i32, neg: bool);
+}
+
+impl LaurentMatrix for Matrix {
+    fn dim(&self) -> usize {
+        self.dim
+    }
+
+    fn zero_like(&self) -> Matrix {
+        Matrix::zero(self.dim, self.p)
+    }
+
+    fn add_column(&mut self, dst: usize, other: &Matrix, src: usize, power: i32, neg: bool) {
+        let dim = self.dim;
+        for row in 0..dim {
+            let x = other.get(row, src);
+            if x.is_zero() {
+                continue;
+            }
+            let target = &mut self.d[row * dim + dst];
+            let summand = x.mult(power, neg);
+            *target = if target.is_zero() {
+                summand
+            } else {
+                &*target + &summand
+            };
+        }
+    }
+}
+
+/// Prints the matrix with one bracketed row per line.
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..self.dim {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            let entries: Vec<String> = (0..self.dim).map(|col| self.get(row, col).to_string()).collect();
+            write!(f, "[{}]", entries.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Mul for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: &Matrix) -> Matrix {
+        assert_eq!(self.dim, rhs.dim, "cannot multiply matrices of different dimensions");
+        let dim = self.dim;
+        let mut res = Matrix::zero(dim, self.p);
+        for i in 0..dim {
+            for k in 0..dim {
+                let x = self.get(i, k);
+                if x.is_zero() {
+                    continue;
+                }
+                for j in 0..dim {
+                    let y = rhs.get(k, j);
+                    if y.is_zero() {
+                        continue;
+                    }
+                    let entry = &mut res.d[i * dim + j];
+                    *entry = &*entry + &(x * y);
+                }
+            }
+        }
+        res
+    }
+}
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: Matrix) -> Matrix {
+        &self * &rhs
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg32;
 
     #[test]
     fn add_handles_overlap() {
-        let a_keys: Vec<u32> = vec![2, 4, 8];
-        let b_keys: Vec<u32> = vec![1, 4, 8];
-        let a_vals: Vec<u8> = vec![10, 20, 30];
-        let b_vals: Vec<u8> = vec![5, 3, 1];
+        let a_keys: Vec<i32> = vec![2, 4, 8];
+        let b_keys: Vec<i32> = vec![1, 4, 8];
+        let a_vals: Vec<u32> = vec![10, 20, 30];
+        let b_vals: Vec<u32> = vec![5, 3, 1];
         let (c_keys, c_vals) = add(&a_keys, &a_vals, &b_keys, &b_vals, 41);
         assert_eq!(c_keys, vec![1, 2, 4, 8]);
         assert_eq!(c_vals, vec![5, 10, 23, 31]);
@@ -204,6 +725,80 @@ mod tests {
         assert!(c.is_zero());
     }
 
+    #[test]
+    fn add_polynomial_large_prime() {
+        let a = Polynomial::new(vec![(0, 200), (1, 150)], 257);
+        let b = Polynomial::new(vec![(0, 100), (1, 107)], 257);
+        assert_eq!(&a + &b, Polynomial::new(vec![(0, 43)], 257));
+        let p = 4294967291;
+        let a = Polynomial::new(vec![(0, p - 1)], p);
+        assert_eq!(&a + &a, Polynomial::new(vec![(0, p - 2)], p));
+    }
+
+    #[test]
+    fn mul_polynomial_large_prime() {
+        let p = 65537;
+        let a = Polynomial::new(vec![(0, p - 1), (1, 65000)], p);
+        let b = Polynomial::new(vec![(0, p - 1), (2, 2)], p);
+        // (-1 + 65000v)(-1 + 2v^2) = 1 - 65000v - 2v^2 + 130000v^3
+        let expected = Polynomial::new(vec![(0, 1), (1, 537), (2, p - 2), (3, 130000 % p)], p);
+        assert_eq!(&a * &b, expected);
+        let p = 4294967291;
+        let a = Polynomial::new(vec![(0, p - 1), (1, p - 1)], p);
+        assert_eq!(&a * &a, Polynomial::new(vec![(0, 1), (1, 2), (2, 1)], p));
+    }
+
+    #[test]
+    fn primality() {
+        let primes: Vec<u32> = (0..30).filter(|x| is_prime(*x)).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(is_prime(65537));
+        assert!(is_prime(4294967291));
+        assert!(!is_prime(4294967295));
+    }
+
+    #[test]
+    fn mul_polynomial_small() {
+        // (1 + 2v)(3 + v^2) = 3 + 6v + v^2 + 2v^3
+        let a = Polynomial::new(vec![(0, 1), (1, 2)], 5);
+        let b = Polynomial::new(vec![(0, 3), (2, 1)], 5);
+        let c = &a * &b;
+        assert_eq!(c, Polynomial::new(vec![(0, 3), (1, 1), (2, 1), (3, 2)], 5));
+    }
+
+    #[test]
+    fn mul_polynomial_mod_two() {
+        let a = Polynomial::new(vec![(3, 1), (4, 1)], 2);
+        assert_eq!(&a * &a, Polynomial::new(vec![(6, 1), (8, 1)], 2));
+        assert!((&a * &Polynomial::zero(2)).is_zero());
+    }
+
+    #[test]
+    fn mul_polynomial_sparse_spread() {
+        let a = Polynomial::new(vec![(0, 1), (1000, 2)], 7);
+        let b = Polynomial::new(vec![(5, 3), (2000, 4)], 7);
+        let c = &a * &b;
+        assert_eq!(c, Polynomial::new(vec![(5, 3), (1005, 6), (2000, 4), (3000, 1)], 7));
+        // Enough terms for Karatsuba, but far too spread out to make dense.
+        let d = Polynomial::new((0..40).map(|i| (i << 24, 1)).collect(), 7);
+        assert_eq!(&d * &d, mul_sparse(&d, &d));
+    }
+
+    #[test]
+    fn karatsuba_matches_schoolbook() {
+        let mut rng = Pcg32::seed_from_u64(0);
+        let p = 251;
+        for (len_a, len_b) in [(40, 40), (100, 37), (257, 300)] {
+            let random_poly = |len: usize, rng: &mut Pcg32| {
+                let terms = (0..len).map(|i| (i as i32 + 3, rng.gen_range(1..p))).collect();
+                Polynomial::new(terms, p)
+            };
+            let a = random_poly(len_a, &mut rng);
+            let b = random_poly(len_b, &mut rng);
+            assert_eq!(&a * &b, mul_sparse(&a, &b));
+        }
+    }
+
     #[test]
     fn zero_polynomial_is_zero() {
         let a = Polynomial::zero(41);
@@ -216,9 +811,141 @@ mod tests {
         assert!(!a.is_zero());
     }
 
+    #[test]
+    fn mul_matrix_by_identity() {
+        let mut mat = Matrix::zero(2, 5);
+        mat.d[0] = Polynomial::new(vec![(0, 1), (2, 3)], 5);
+        mat.d[3] = Polynomial::new(vec![(1, 4)], 5);
+        assert_eq!(&mat * &Matrix::identity(2, 5), mat);
+        assert_eq!(&Matrix::identity(2, 5) * &mat, mat);
+    }
+
+    #[test]
+    fn mul_matrix_two_by_two() {
+        let mut a = Matrix::zero(2, 5);
+        a.d[0] = Polynomial::new(vec![(0, 1)], 5);
+        a.d[1] = Polynomial::new(vec![(1, 1)], 5);
+        a.d[3] = Polynomial::new(vec![(0, 2)], 5);
+        let mut b = Matrix::zero(2, 5);
+        b.d[0] = Polynomial::new(vec![(1, 3)], 5);
+        b.d[2] = Polynomial::new(vec![(0, 1)], 5);
+        b.d[3] = Polynomial::new(vec![(2, 1)], 5);
+        let c = &a * &b;
+        assert_eq!(c.d[0], Polynomial::new(vec![(1, 4)], 5));
+        assert_eq!(c.d[1], Polynomial::new(vec![(3, 1)], 5));
+        assert_eq!(c.d[2], Polynomial::new(vec![(0, 2)], 5));
+        assert_eq!(c.d[3], Polynomial::new(vec![(2, 2)], 5));
+    }
+
+    #[test]
+    fn scalar_matrices() {
+        assert!(Matrix::identity(3, 5).is_scalar());
+        let mut mat = Matrix::zero(3, 5);
+        for i in 0..3 {
+            mat.d[i * 3 + i] = Polynomial::new(vec![(3, 2)], 5);
+        }
+        assert!(mat.is_scalar());
+        mat.d[4] = Polynomial::new(vec![(3, 1)], 5);
+        assert_eq!(mat.projlen(), 1);
+        assert!(!mat.is_scalar());
+        assert!(!Matrix::zero(3, 5).is_scalar());
+    }
+
+    #[test]
+    fn monomial_matrix_is_not_scalar() {
+        let mut mat = Matrix::zero(2, 5);
+        mat.d[1] = Polynomial::new(vec![(1, 1)], 5);
+        mat.d[2] = Polynomial::new(vec![(1, 1)], 5);
+        assert_eq!(mat.projlen(), 1);
+        assert!(!mat.is_scalar());
+    }
+
+    #[test]
+    fn inverses_mod_p() {
+        for p in [2, 3, 5, 257] {
+            for x in 1..p {
+                assert_eq!(x * inverse_mod(x, p) % p, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_form_is_projective() {
+        let mut mat = Matrix::zero(2, 5);
+        mat.d[1] = Polynomial::new(vec![(2, 3), (4, 1)], 5);
+        mat.d[2] = Polynomial::new(vec![(3, 2)], 5);
+        let canonical = mat.canonical();
+        assert_eq!(canonical.d[1], Polynomial::new(vec![(0, 1), (2, 2)], 5));
+        assert_eq!(canonical.d[2], Polynomial::new(vec![(1, 4)], 5));
+
+        let mut multiple = Matrix::zero(2, 5);
+        for i in 0..4 {
+            multiple.d[i] = mat.d[i].mult(7, true);
+        }
+        assert_ne!(multiple, mat);
+        assert_eq!(multiple.canonical(), canonical);
+    }
+
+    #[test]
+    fn display_polynomials_and_matrices() {
+        let poly = Polynomial::new(vec![(0, 1), (1, 4), (2, 1), (3, 2)], 5);
+        assert_eq!(poly.to_string(), "1 + 4v + v^2 + 2v^3");
+        assert_eq!(Polynomial::zero(5).to_string(), "0");
+        let mut mat = Matrix::identity(2, 5);
+        mat.d[1] = poly;
+        assert_eq!(mat.to_string(), "[1, 1 + 4v + v^2 + 2v^3]\n[0, 1]");
+    }
+
+    #[test]
+    fn laurent_polynomials() {
+        let poly = Polynomial::new(vec![(-3, 1), (0, 2), (2, 4)], 5);
+        assert_eq!((poly.min_power(), poly.max_power()), (-3, 2));
+        assert_eq!(poly.to_string(), "v^-3 + 2 + 4v^2");
+        let inverse = Polynomial::one(5).mult(-2, false);
+        let product = &poly * &inverse;
+        assert_eq!(product, Polynomial::new(vec![(-5, 1), (-2, 2), (0, 4)], 5));
+        assert_eq!(product.mult(5, false).min_power(), 0);
+        let mut mat = Matrix::identity(2, 5);
+        mat.d[1] = poly;
+        assert_eq!(mat.projlen(), 6);
+    }
+
+    #[test]
+    fn dense_matrices() {
+        for p in [2, 3, 5] {
+            let mut mat = Matrix::identity(2, p);
+            mat.d[1] = Polynomial::new(vec![(-2, 1), (1, p - 1)], p);
+            mat.d[3] = Polynomial::new(vec![(0, p - 1)], p);
+            let dense = DenseMatrix::from(&mat);
+            assert_eq!((dense.base, dense.width), (-2, 4));
+            let entry: Vec<u32> = (0..4).map(|k| dense.coef(0, 1, k)).collect();
+            assert_eq!(entry, [1, 0, 0, p - 1]);
+            assert_eq!(dense.projlen(), mat.projlen());
+            assert_eq!(Matrix::from(&dense), mat);
+            assert_eq!(Matrix::from(&dense.canonical()), mat.canonical());
+            assert_eq!(DenseMatrix::from(&mat.canonical()), dense.canonical());
+            assert!(!dense.is_scalar());
+            let mut wide = DenseMatrix::zero(3, p);
+            wide.reset(0, 100);
+            for i in 0..3 {
+                wide.coefs.set(wide.stride(), i * 3 + i, 70, p - 1);
+            }
+            wide.trim();
+            let mut scalar = Matrix::identity(3, p);
+            for i in 0..3 {
+                scalar.d[i * 3 + i] = Polynomial::new(vec![(70, p - 1)], p);
+            }
+            assert_eq!(wide, DenseMatrix::from(&scalar));
+            assert!(wide.is_scalar());
+            assert_eq!(Matrix::from(&DenseMatrix::zero(2, p)), Matrix::zero(2, p));
+        }
+    }
+
     #[test]
     fn identity_matrix_projlen() {
-        let mat = Matrix::identity(41);
+        let mat = Matrix::identity(3, 41);
+        assert_eq!(mat.projlen(), 1);
+        let mat = Matrix::identity(5, 41);
         assert_eq!(mat.projlen(), 1);
     }
 }
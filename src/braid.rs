@@ -0,0 +1,271 @@
+//! Braids as elements of B_n, stored in left normal form Δ^k·A1⋯Ar, with
+//! the group operations carried out through the Garside structure. Two braids
+//! are equal exactly when their normal forms are, which solves the word
+//! problem.
+
+use hashbrown::HashMap;
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Mul;
+
+use crate::algebra::Matrix;
+use crate::garside::{burau_image, integral_burau_image, left_normal_form, quotient, simple_element, ActionTable};
+use crate::integral::IntMatrix;
+use crate::permutation::Permutation;
+use crate::word::{artin_word, normal_form};
+
+#[derive(Clone, Debug)]
+pub struct Braid {
+    n: usize,
+    delta_power: i32,
+    factors: Vec<u32>,
+    /// Burau images computed so far, by prime.
+    images: RefCell<HashMap<u32, Matrix>>,
+}
+
+impl Braid {
+    /// The braid Δ^delta_power·x1⋯xm for arbitrary factors xi.
+    pub fn new(n: usize, delta_power: i32, factors: &[u32]) -> Braid {
+        Braid::from_simples(n, delta_power, &conjugate_by_delta(n, factors, 0))
+    }
+
+    pub fn identity(n: usize) -> Braid {
+        Braid::new(n, 0, &[])
+    }
+
+    /// The power Δ^k of the half twist.
+    pub fn delta(n: usize, k: i32) -> Braid {
+        Braid::new(n, k, &[])
+    }
+
+    /// The braid given by a word in the Artin generators.
+    pub fn from_word(n: usize, word: &[i32]) -> Braid {
+        let (delta_power, factors) = normal_form(n, word);
+        Braid::new(n, delta_power, &factors)
+    }
+
+    /// A word in the Artin generators for the braid.
+    pub fn word(&self) -> Vec<i32> {
+        artin_word(self.n, self.delta_power, &self.factors)
+    }
+
+    pub fn strands(&self) -> usize {
+        self.n
+    }
+
+    /// The ids of the factors A1, …, Ar of the left normal form.
+    pub fn factors(&self) -> &[u32] {
+        &self.factors
+    }
+
+    /// The infimum, the largest k with Δ^k ≤ x, which is the power of Δ in
+    /// the left normal form.
+    pub fn inf(&self) -> i32 {
+        self.delta_power
+    }
+
+    /// The supremum, the smallest k with x ≤ Δ^k.
+    pub fn sup(&self) -> i32 {
+        self.delta_power + self.factors.len() as i32
+    }
+
+    /// The number of factors of the left normal form other than Δ.
+    pub fn canonical_length(&self) -> usize {
+        self.factors.len()
+    }
+
+    pub fn inverse(&self) -> Braid {
+        // (Δ^k·A)⁻¹ = A⁻¹·Δ^-k = Δ^-k·τ^k(A⁻¹), and quotient gives the left
+        // normal form Δ^j·F of A⁻¹.
+        let (j, factors) = quotient(self.n, &[], &self.factors);
+        let simples = conjugate_by_delta(self.n, &factors, self.delta_power);
+        Braid::from_simples(self.n, j - self.delta_power, &simples)
+    }
+
+    /// The conjugate other⁻¹·x·other.
+    pub fn conjugate(&self, other: &Braid) -> Braid {
+        &(&other.inverse() * self) * other
+    }
+
+    /// Reduced Burau image mod p, as computed by `garside::burau_image`. The
+    /// image is computed on first use and kept.
+    pub fn burau_image(&self, table: &ActionTable, p: u32) -> Matrix {
+        assert_eq!(table.n, self.n, "action table of B_{} used for a braid in B_{}", table.n, self.n);
+        if let Some(image) = self.images.borrow().get(&p) {
+            return image.clone();
+        }
+        let image = burau_image(self.delta_power, &self.factors, table, p).unwrap();
+        self.images.borrow_mut().insert(p, image.clone());
+        image
+    }
+
+    /// Reduced Burau image over Z[v, v⁻¹], as computed by
+    /// `garside::integral_burau_image`.
+    pub fn integral_burau_image(&self, table: &ActionTable) -> IntMatrix {
+        assert_eq!(table.n, self.n, "action table of B_{} used for a braid in B_{}", table.n, self.n);
+        integral_burau_image(self.delta_power, &self.factors, table).unwrap()
+    }
+
+    fn from_simples(n: usize, delta_power: i32, simples: &[Permutation]) -> Braid {
+        let (delta_power, factors) = left_normal_form(n, delta_power, simples);
+        Braid {
+            n,
+            delta_power,
+            factors,
+            images: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+/// The simple elements of the factors conjugated by Δ^k, that is, with τ^k
+/// applied to each of them.
+fn conjugate_by_delta(n: usize, factors: &[u32], k: i32) -> Vec<Permutation> {
+    factors
+        .iter()
+        .map(|x| {
+            let simple = simple_element(n, *x);
+            if k % 2 == 0 {
+                simple
+            } else {
+                simple.flip()
+            }
+        })
+        .collect()
+}
+
+impl Mul for &Braid {
+    type Output = Braid;
+
+    fn mul(self, rhs: &Braid) -> Braid {
+        assert_eq!(self.n, rhs.n, "cannot multiply braids with different numbers of strands");
+        // Δ^a·A·Δ^b·B = Δ^(a + b)·τ^b(A)·B.
+        let mut simples = conjugate_by_delta(self.n, &self.factors, rhs.delta_power);
+        simples.extend(rhs.factors.iter().map(|x| simple_element(self.n, *x)));
+        Braid::from_simples(self.n, self.delta_power + rhs.delta_power, &simples)
+    }
+}
+
+impl Mul for Braid {
+    type Output = Braid;
+
+    fn mul(self, rhs: Braid) -> Braid {
+        &self * &rhs
+    }
+}
+
+impl PartialEq for Braid {
+    fn eq(&self, other: &Braid) -> bool {
+        self.n == other.n && self.delta_power == other.delta_power && self.factors == other.factors
+    }
+}
+
+impl Eq for Braid {}
+
+/// Prints the left normal form as `Δ^k·[A1, …, Ar]`.
+impl fmt::Display for Braid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Δ^{}·{:?}", self.delta_power, self.factors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn braids() -> Vec<Braid> {
+        let words: [&[i32]; 6] = [&[], &[1], &[-2], &[1, 2, -1, 3, -2, -2, 1], &[-3, -3, 2, 1, 1, -2], &[3, 2, 1, 1, 2, 3]];
+        words.iter().map(|x| Braid::from_word(4, x)).collect()
+    }
+
+    #[test]
+    fn group_axioms() {
+        let e = Braid::identity(4);
+        for a in &braids() {
+            assert_eq!(&(a * &e), a);
+            assert_eq!(&(&e * a), a);
+            assert_eq!(a * &a.inverse(), e);
+            assert_eq!(&a.inverse() * a, e);
+            assert_eq!(&a.inverse().inverse(), a);
+            for b in &braids() {
+                assert_eq!((a * b).inverse(), &b.inverse() * &a.inverse());
+                for c in &braids() {
+                    assert_eq!(&(a * b) * c, a * &(b * c));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn products_agree_with_words() {
+        for a in &braids() {
+            for b in &braids() {
+                let mut word = a.word();
+                word.extend(b.word());
+                assert_eq!(a * b, Braid::from_word(4, &word));
+            }
+        }
+        assert_eq!(Braid::from_word(4, &[1, 2, 1]), Braid::from_word(4, &[2, 1, 2]));
+        assert_ne!(Braid::from_word(4, &[1, 2]), Braid::from_word(4, &[2, 1]));
+    }
+
+    #[test]
+    fn delta_powers_and_conjugation() {
+        let full_twist = Braid::delta(4, 2);
+        let delta = Braid::delta(4, 1);
+        for a in &braids() {
+            assert_eq!(a.conjugate(&full_twist), *a);
+            assert_eq!(a * &full_twist, &full_twist * a);
+            let flipped = a.conjugate(&delta);
+            assert_eq!(flipped.conjugate(&delta), *a);
+            assert_eq!(flipped.canonical_length(), a.canonical_length());
+        }
+        let sigma1 = Braid::from_word(4, &[1]);
+        assert_eq!(sigma1.conjugate(&delta), Braid::from_word(4, &[3]));
+        let b = Braid::from_word(4, &[2, 3]);
+        assert_eq!(sigma1.conjugate(&b), Braid::from_word(4, &[-3, -2, 1, 2, 3]));
+    }
+
+    #[test]
+    fn inf_sup_and_canonical_length() {
+        let sigma1_inverse = Braid::from_word(4, &[-1]);
+        assert_eq!((sigma1_inverse.inf(), sigma1_inverse.sup()), (-1, 0));
+        assert_eq!(sigma1_inverse.factors(), &[17]);
+        let a = Braid::from_word(4, &[1, 2, 3, 1, 2, 1, 2, 1]);
+        assert_eq!((a.inf(), a.sup(), a.canonical_length()), (1, 2, 1));
+        assert_eq!(Braid::delta(4, -3).sup(), -3);
+        assert_eq!(a.to_string(), "Δ^1·[12]");
+    }
+
+    #[test]
+    fn burau_image_is_a_homomorphism() {
+        let table = ActionTable::new(4);
+        for a in &braids() {
+            for b in &braids() {
+                let product = &a.burau_image(&table, 5) * &b.burau_image(&table, 5);
+                assert_eq!((a * b).burau_image(&table, 5), product);
+            }
+            let inverse = a.inverse().burau_image(&table, 5);
+            assert_eq!(&a.burau_image(&table, 5) * &inverse, Matrix::identity(3, 5));
+        }
+        let kernel = Braid::new(4, 0, &[10, 13, 10, 13, 10, 13, 10, 13]);
+        assert!(kernel.burau_image(&table, 2).is_scalar());
+        assert!(!kernel.burau_image(&table, 3).is_scalar());
+    }
+
+    #[test]
+    fn integral_burau_image_reduces_to_images_mod_p() {
+        let table = ActionTable::new(4);
+        for a in &braids() {
+            let image = a.integral_burau_image(&table);
+            for p in [2, 3, 5, 4294967291] {
+                assert_eq!(image.reduce(p), a.burau_image(&table, p));
+            }
+            for b in &braids() {
+                assert_eq!((a * b).integral_burau_image(&table), &image * &b.integral_burau_image(&table));
+            }
+        }
+        assert!(Braid::delta(4, -2).integral_burau_image(&table).is_scalar());
+        let kernel = Braid::new(4, 0, &[10, 13, 10, 13, 10, 13, 10, 13]);
+        assert!(!kernel.integral_burau_image(&table).is_scalar());
+    }
+}
@@ -0,0 +1,446 @@
+//! Checkpoints of long-running searches. A checkpoint holds the frontier of
+//! a search, with the factors and Burau image of every state, together with
+//! the number of layers handled so far and the seed of the random stream for
+//! the next layer, so that a resumed search continues exactly as the original
+//! one would have.
+//!
+//! The file format is little-endian binary:
+//!
+//! - the magic bytes `BURAUCKP` and the format version as a `u32`,
+//! - the strategy name as a `u32` length followed by UTF-8 bytes,
+//! - `p` and `n` as `u32`, and the seed, layer and layer seed as `u64`,
+//! - the number of states as a `u64`, and for every state the number of
+//!   factors as a `u32`, the factors as `u32`, and the `(n - 1)^2` entries of
+//!   the matrix in row-major order, each as a `u32` number of terms followed
+//!   by the power of every term as `i32` and its coefficient as `u32`.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::algebra::{is_prime, Matrix, Polynomial};
+use crate::garside::{self, ActionTable, MAX_STRANDS, MIN_STRANDS};
+use crate::progress;
+use crate::report::Event;
+use crate::search::State;
+
+const MAGIC: &[u8; 8] = b"BURAUCKP";
+const VERSION: u32 = 1;
+/// Longest strategy name accepted when reading a checkpoint.
+const MAX_STRATEGY_LEN: u32 = 64;
+
+/// The frontier of a search, and everything else needed to resume it.
+#[derive(Clone)]
+pub struct Checkpoint {
+    pub strategy: String,
+    pub p: u32,
+    pub n: usize,
+    /// Seed given to the original run.
+    pub seed: u64,
+    /// Number of layers handled so far.
+    pub layer: u64,
+    /// Seed of the random stream of the next layer.
+    pub layer_seed: u64,
+    pub states: Vec<State>,
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckpointError::Io(e) => write!(f, "{}", e),
+            CheckpointError::Format(e) => write!(f, "invalid checkpoint: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+impl From<io::Error> for CheckpointError {
+    fn from(e: io::Error) -> CheckpointError {
+        CheckpointError::Io(e)
+    }
+}
+
+fn write_u32(w: &mut impl Write, x: u32) -> io::Result<()> {
+    w.write_all(&x.to_le_bytes())
+}
+
+fn write_u64(w: &mut impl Write, x: u64) -> io::Result<()> {
+    w.write_all(&x.to_le_bytes())
+}
+
+fn write_i32(w: &mut impl Write, x: i32) -> io::Result<()> {
+    w.write_all(&x.to_le_bytes())
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_i32(r: &mut impl Read) -> io::Result<i32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint to `path`, replacing any existing file only once
+    /// the new one is complete.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let mut w = BufWriter::new(File::create(&tmp)?);
+        w.write_all(MAGIC)?;
+        write_u32(&mut w, VERSION)?;
+        write_u32(&mut w, self.strategy.len() as u32)?;
+        w.write_all(self.strategy.as_bytes())?;
+        write_u32(&mut w, self.p)?;
+        write_u32(&mut w, self.n as u32)?;
+        write_u64(&mut w, self.seed)?;
+        write_u64(&mut w, self.layer)?;
+        write_u64(&mut w, self.layer_seed)?;
+        write_u64(&mut w, self.states.len() as u64)?;
+        for state in &self.states {
+            let factors = state.factors.to_vec();
+            write_u32(&mut w, factors.len() as u32)?;
+            for factor in &factors {
+                write_u32(&mut w, *factor)?;
+            }
+            for entry in &Matrix::from(&state.mat).d {
+                write_u32(&mut w, entry.powers.len() as u32)?;
+                for (power, coef) in entry.powers.iter().zip(&entry.coefs) {
+                    write_i32(&mut w, *power)?;
+                    write_u32(&mut w, *coef)?;
+                }
+            }
+        }
+        w.into_inner()?.sync_all()?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Reads a checkpoint written by `write`, rejecting it unless the matrix
+    /// of every state is the Burau image of its factors.
+    pub fn read(path: &Path) -> Result<Checkpoint, CheckpointError> {
+        let mut r = BufReader::new(File::open(path)?);
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(CheckpointError::Format("not a checkpoint file".to_string()));
+        }
+        let version = read_u32(&mut r)?;
+        if version != VERSION {
+            return Err(CheckpointError::Format(format!(
+                "unsupported version {}, expected {}",
+                version, VERSION
+            )));
+        }
+        let strategy_len = read_u32(&mut r)?;
+        if strategy_len > MAX_STRATEGY_LEN {
+            return Err(CheckpointError::Format(format!("strategy name of {} bytes", strategy_len)));
+        }
+        let mut strategy = vec![0; strategy_len as usize];
+        r.read_exact(&mut strategy)?;
+        let strategy = String::from_utf8(strategy)
+            .map_err(|_| CheckpointError::Format("strategy is not UTF-8".to_string()))?;
+        let p = read_u32(&mut r)?;
+        if !is_prime(p) {
+            return Err(CheckpointError::Format(format!("modulus {} is not a prime", p)));
+        }
+        let n = read_u32(&mut r)? as usize;
+        if !(MIN_STRANDS..=MAX_STRANDS).contains(&n) {
+            return Err(CheckpointError::Format(format!("invalid number of strands {}", n)));
+        }
+        let seed = read_u64(&mut r)?;
+        let layer = read_u64(&mut r)?;
+        let layer_seed = read_u64(&mut r)?;
+        let num_states = read_u64(&mut r)?;
+        let dim = n - 1;
+        let table = ActionTable::new(n);
+        let mut states = Vec::new();
+        for _ in 0..num_states {
+            let num_factors = read_u32(&mut r)?;
+            if num_factors == 0 {
+                return Err(CheckpointError::Format("state without factors".to_string()));
+            }
+            let factors = (0..num_factors)
+                .map(|_| read_u32(&mut r))
+                .collect::<io::Result<Vec<u32>>>()?;
+            if let Some(x) = factors.iter().find(|x| **x == 0 || **x > garside::num_factors(n)) {
+                return Err(CheckpointError::Format(format!("unknown factor {} of B_{}", x, n)));
+            }
+            let mut mat = Matrix::zero(dim, p);
+            for entry in mat.d.iter_mut() {
+                let num_terms = read_u32(&mut r)?;
+                let mut terms = Vec::new();
+                for _ in 0..num_terms {
+                    let (power, coef) = (read_i32(&mut r)?, read_u32(&mut r)?);
+                    if coef == 0 || coef >= p {
+                        return Err(CheckpointError::Format(format!("coefficient {} mod {}", coef, p)));
+                    }
+                    if terms.last().is_some_and(|x: &(i32, u32)| x.0 >= power) {
+                        return Err(CheckpointError::Format("powers out of order".to_string()));
+                    }
+                    terms.push((power, coef));
+                }
+                *entry = Polynomial::new(terms, p);
+            }
+            // The image is rebuilt rather than trusted, as a corrupt matrix
+            // would silently mislead the search.
+            let state = State::from_factors(&factors, &table, p);
+            if Matrix::from(&state.mat) != mat {
+                return Err(CheckpointError::Format(format!(
+                    "matrix of state {:?} is not its Burau image",
+                    factors
+                )));
+            }
+            states.push(state);
+        }
+        Ok(Checkpoint {
+            strategy,
+            p,
+            n,
+            seed,
+            layer,
+            layer_seed,
+            states,
+        })
+    }
+}
+
+/// Keeps track of the layers handled by a search and writes a checkpoint
+/// at the end of a layer whenever `interval` has passed since the last one.
+pub struct Checkpoints {
+    path: Option<PathBuf>,
+    interval: Duration,
+    last_write: Instant,
+    strategy: String,
+    p: u32,
+    n: usize,
+    seed: u64,
+    /// Number of layers handled so far, including those handled before the
+    /// search was resumed.
+    pub layer: u64,
+    /// Seed of the random stream of the current layer.
+    pub layer_seed: u64,
+}
+
+impl Checkpoints {
+    /// Checkpoints of a new search, written to `path` if given.
+    pub fn new(
+        path: Option<PathBuf>,
+        interval: Duration,
+        strategy: &str,
+        p: u32,
+        n: usize,
+        seed: u64,
+    ) -> Checkpoints {
+        Checkpoints {
+            path,
+            interval,
+            last_write: Instant::now(),
+            strategy: strategy.to_string(),
+            p,
+            n,
+            seed,
+            layer: 0,
+            layer_seed: seed,
+        }
+    }
+
+    /// Checkpoints of a search resumed from `checkpoint`. Only the seed of
+    /// the next layer is restored, so a resumed search repeats the original
+    /// one only if its queues start every layer afresh, as those of the
+    /// strategies supporting checkpoints do.
+    pub fn resume(checkpoint: &Checkpoint, path: Option<PathBuf>, interval: Duration) -> Checkpoints {
+        Checkpoints {
+            layer: checkpoint.layer,
+            layer_seed: checkpoint.layer_seed,
+            ..Checkpoints::new(
+                path,
+                interval,
+                &checkpoint.strategy,
+                checkpoint.p,
+                checkpoint.n,
+                checkpoint.seed,
+            )
+        }
+    }
+
+    /// Seed given to the original run.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Records the end of a layer after which the frontier is `states` and the
+    /// next layer uses the random stream seeded by `layer_seed`. Failing to
+    /// write a checkpoint is reported, but does not stop the search.
+    pub fn finish_layer<'a>(&mut self, layer_seed: u64, states: impl IntoIterator<Item = &'a State>) {
+        self.layer += 1;
+        self.layer_seed = layer_seed;
+        let Some(path) = &self.path else {
+            return;
+        };
+        if self.last_write.elapsed() < self.interval {
+            return;
+        }
+        let checkpoint = Checkpoint {
+            strategy: self.strategy.clone(),
+            p: self.p,
+            n: self.n,
+            seed: self.seed,
+            layer: self.layer,
+            layer_seed,
+            states: states.into_iter().cloned().collect(),
+        };
+        match checkpoint.write(path) {
+            Ok(()) => {
+                progress!(
+                    "Wrote checkpoint with {} states after layer {} to {}",
+                    checkpoint.states.len(),
+                    self.layer,
+                    path.display()
+                );
+                Event::new("checkpoint")
+                    .field("layer", self.layer)
+                    .field("states", checkpoint.states.len())
+                    .string("path", &path.display().to_string())
+                    .emit();
+            }
+            Err(e) => eprintln!("warning: could not write checkpoint to {}: {}", path.display(), e),
+        }
+        self.last_write = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::findings::Findings;
+    use crate::search::{beam_search, bucket_by_projlen, initial_states, Run};
+    use crate::transposition::TranspositionTable;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("burau4_mod_p_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn checkpoint_round_trip() {
+        let table = ActionTable::new(4);
+        let states = vec![
+            State::from_factors(&[13, 10, 13], &table, 5),
+            State::from_factors(&[17, 19, 10], &table, 5),
+        ];
+        let checkpoint = Checkpoint {
+            strategy: "beam".to_string(),
+            p: 5,
+            n: 4,
+            seed: 3,
+            layer: 7,
+            layer_seed: 11,
+            states,
+        };
+        let path = temp_path("round_trip");
+        checkpoint.write(&path).unwrap();
+        let read = Checkpoint::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read.strategy, "beam");
+        assert_eq!((read.p, read.n, read.seed, read.layer, read.layer_seed), (5, 4, 3, 7, 11));
+        for (a, b) in read.states.iter().zip(&checkpoint.states) {
+            assert_eq!(a.factors, b.factors);
+            assert_eq!(a.mat, b.mat);
+            assert_eq!(a.span, b.span);
+        }
+    }
+
+    #[test]
+    fn read_rejects_other_files() {
+        let path = temp_path("other");
+        fs::write(&path, b"BURAUCKP\x02\x00\x00\x00").unwrap();
+        let version = Checkpoint::read(&path);
+        fs::write(&path, b"not a checkpoint").unwrap();
+        let magic = Checkpoint::read(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(version, Err(CheckpointError::Format(_))));
+        assert!(matches!(magic, Err(CheckpointError::Format(_))));
+    }
+
+    #[test]
+    fn read_rejects_corrupt_checkpoints() {
+        let table = ActionTable::new(4);
+        let state = State::from_factors(&[13, 10, 13], &table, 5);
+        assert!(!Matrix::from(&state.mat).d[0].is_zero());
+        let checkpoint = Checkpoint {
+            strategy: "beam".to_string(),
+            p: 5,
+            n: 4,
+            seed: 3,
+            layer: 7,
+            layer_seed: 11,
+            states: vec![state],
+        };
+        let path = temp_path("corrupt");
+        checkpoint.write(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        // Offsets of the strategy length, p, n, the first factor, the first
+        // power and the first coefficient.
+        let power = i32::from_le_bytes(bytes[80..84].try_into().unwrap());
+        let coef = u32::from_le_bytes(bytes[84..88].try_into().unwrap());
+        for (offset, value) in [
+            (12, u32::MAX),
+            (20, 4),
+            (24, 2),
+            (24, 8),
+            (64, 23),
+            (64, 10),
+            (80, i32::MIN as u32),
+            (80, (power - 1) as u32),
+            (84, 5),
+            (84, coef % 4 + 1),
+        ] {
+            let mut corrupt = bytes.clone();
+            corrupt[offset..offset + 4].copy_from_slice(&u32::to_le_bytes(value));
+            fs::write(&path, &corrupt).unwrap();
+            assert!(matches!(Checkpoint::read(&path), Err(CheckpointError::Format(_))));
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resumed_search_is_deterministic() {
+        let table = ActionTable::new(4);
+        let path = temp_path("resume");
+        let mut run = Run::new(
+            TranspositionTable::new(0),
+            Checkpoints::new(Some(path.clone()), Duration::ZERO, "beam", 2, 4, 1),
+            Findings::new(1, None, 2, 4, 1),
+        );
+        beam_search(initial_states(&table, 2), 50, &mut run, 4);
+
+        let checkpoint = Checkpoint::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(checkpoint.layer > 0);
+        let mut resumed = Run::new(
+            TranspositionTable::new(0),
+            Checkpoints::resume(&checkpoint, None, Duration::ZERO),
+            Findings::new(1, None, 2, 4, 1),
+        );
+        beam_search(bucket_by_projlen(checkpoint.states), 50, &mut resumed, 4);
+        assert_eq!(resumed.findings.found()[0].factors, run.findings.found()[0].factors);
+    }
+}
@@ -0,0 +1,581 @@
+use burau4_mod_p::algebra::is_prime;
+use burau4_mod_p::garside::{MAX_STRANDS, MIN_STRANDS};
+use burau4_mod_p::word::Notation;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+Search for kernel elements of the Burau representation of B_n modulo p.
+
+Usage: burau4_mod_p [OPTIONS] <P>
+       burau4_mod_p verify [-n <N>] <P> <FACTORS>
+       burau4_mod_p word [OPTIONS] <FACTORS>
+       burau4_mod_p normal-form [-n <N>] <P> <WORD>
+
+Arguments:
+  <P>                          Prime modulus, at most 4294967291
+
+Search options:
+  -s, --strategy <NAME>        Search strategy [default: best-first-parallel]
+                                 best-first-parallel  best-first search, expanding
+                                                      the lowest projlen in parallel
+                                 beam-parallel        beam search over layers, in parallel
+                                 beam                 beam search with random tie-breaking
+                                 best-first-limited   best-first search with a bounded frontier
+                                 reservoir            best-first search with reservoir
+                                                      sampling per projlen
+                                 lookahead            beam search ranked by a lookahead
+                                 collision            two beams looking for braids with equal
+                                                      projective images
+                                 birthday             random walks looking for braids with
+                                                      equal projective images
+  -n, --strands <N>            Number of strands [default: 4]
+      --seed <SEED>            Random seed [default: 0]
+      --keep <N>               best-first-parallel: states kept in the frontier [default: 150000]
+      --handle <N>             best-first-parallel: states expanded per step [default: 20000]
+      --chunk-size <N>         best-first-parallel, beam-parallel: states per parallel task
+                               [default: 16]
+      --layer-width <N>        beam-parallel: states expanded per layer [default: 8000]
+      --beam-width <N>         beam: states kept per layer [default: 250000]
+      --max-keep <N>           best-first-limited: states kept in the frontier [default: 60000]
+      --reservoir-size <N>     reservoir: states kept per projlen [default: 50000]
+      --lookahead-width <N>    lookahead: candidates expanded per layer [default: 15000]
+      --lookahead-depth <N>    lookahead: layers explored to rank a candidate [default: 2]
+      --lookahead-samples <N>  lookahead: rank by this many random walks instead of
+                               exploring every continuation
+      --collision-width <N>    collision: states kept per layer in each beam [default: 10000]
+      --walk-length <N>        birthday: factors in every random walk [default: 200]
+      --max-images <N>         collision, birthday: projective images stored [default: 10000000]
+      --count <N>              Kernel elements to find before stopping, counting elements
+                               equal up to powers of Δ and cyclic conjugation once
+                               [default: 1]
+      --time-limit <SECONDS>   Stop after this many seconds, even with fewer elements
+      --output <FILE>          Append every kernel element found to this CSV file
+      --checkpoint <FILE>      best-first-parallel, beam-parallel, beam, lookahead: write the
+                               frontier to this file at the end of a layer, at most once
+                               per checkpoint interval
+      --checkpoint-interval <SECONDS>
+                               Seconds between checkpoints [default: 600]
+      --resume <FILE>          Continue the search saved in this checkpoint, which also
+                               receives further checkpoints unless --checkpoint is given
+      --tt-memory <MIB>        All but collision, birthday: memory for a table of visited
+                               projective Burau images, used to drop states reaching an
+                               image seen before; 0 disables it [default: 0]
+      --format <FORMAT>        Output format [default: human]
+                                 human  progress as text
+                                 json   one JSON object per line for the start of the
+                                        run, every layer, and every element found
+  -h, --help                   Print this help
+
+Verify arguments:
+  <FACTORS>                    Garside factors as printed by the search, e.g. \"[13, 10, 13, 10]\"
+
+Word options, printing Δ^k·A1⋯Ar as a word in the Artin generators:
+  -n, --strands <N>            Number of strands [default: 4]
+      --delta-power <K>        Power of Δ in front of the factors [default: 0]
+      --notation <NAME>        How to print the word [default: numbers]
+                                 numbers  1 2 -3
+                                 letters  s1 s2 S3
+      --reduce <NAME>          How to simplify the word [default: none]
+                                 none     powers of Δ followed by the positive word
+                                          of every factor
+                                 free     cancel adjacent inverse pairs
+                                 short    absorb powers of Δ⁻¹ into the factors
+
+Normal form arguments, printing the left normal form and Burau image mod p of a braid:
+  <P>                          Prime modulus, or 0 for the Burau image over Z[v, v^-1]
+  <WORD>                       Word in the Artin generators, e.g. \"1 2 -3\", \"s1 s2 S3\"
+                               or \"σ1 σ2 σ3⁻¹\"
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    BestFirstParallel,
+    BeamParallel,
+    Beam,
+    BestFirstLimited,
+    Reservoir,
+    Lookahead,
+    Collision,
+    Birthday,
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Strategy, String> {
+        match s {
+            "best-first-parallel" => Ok(Strategy::BestFirstParallel),
+            "beam-parallel" => Ok(Strategy::BeamParallel),
+            "beam" => Ok(Strategy::Beam),
+            "best-first-limited" => Ok(Strategy::BestFirstLimited),
+            "reservoir" => Ok(Strategy::Reservoir),
+            "lookahead" => Ok(Strategy::Lookahead),
+            "collision" => Ok(Strategy::Collision),
+            "birthday" => Ok(Strategy::Birthday),
+            _ => Err(format!("unknown strategy '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Strategy::BestFirstParallel => "best-first-parallel",
+            Strategy::BeamParallel => "beam-parallel",
+            Strategy::Beam => "beam",
+            Strategy::BestFirstLimited => "best-first-limited",
+            Strategy::Reservoir => "reservoir",
+            Strategy::Lookahead => "lookahead",
+            Strategy::Collision => "collision",
+            Strategy::Birthday => "birthday",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Strategy {
+    /// Whether the strategy proceeds in layers after which its frontier can
+    /// be checkpointed.
+    pub fn supports_checkpoints(&self) -> bool {
+        matches!(
+            self,
+            Strategy::BestFirstParallel | Strategy::BeamParallel | Strategy::Beam | Strategy::Lookahead
+        )
+    }
+
+    /// Whether the strategy drops states using a transposition table. The
+    /// collision searches need every state they reach, so they do not.
+    pub fn supports_transpositions(&self) -> bool {
+        !matches!(self, Strategy::Collision | Strategy::Birthday)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Human,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "human" => Ok(Format::Human),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format '{}'", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub p: u32,
+    pub strands: usize,
+    pub strategy: Strategy,
+    pub seed: u64,
+    pub keep: usize,
+    pub handle: usize,
+    pub chunk_size: usize,
+    pub layer_width: usize,
+    pub beam_width: usize,
+    pub max_keep: usize,
+    pub reservoir_size: usize,
+    pub lookahead_width: usize,
+    pub lookahead_depth: usize,
+    pub lookahead_samples: Option<usize>,
+    pub collision_width: usize,
+    pub walk_length: usize,
+    pub max_images: usize,
+    pub count: usize,
+    pub time_limit: Option<u64>,
+    pub output: Option<PathBuf>,
+    pub checkpoint: Option<PathBuf>,
+    pub checkpoint_interval: u64,
+    pub resume: Option<PathBuf>,
+    pub tt_memory: usize,
+    pub format: Format,
+}
+
+impl SearchOptions {
+    pub fn new(p: u32) -> SearchOptions {
+        SearchOptions {
+            p,
+            strands: 4,
+            strategy: Strategy::BestFirstParallel,
+            seed: 0,
+            keep: 150000,
+            handle: 20000,
+            chunk_size: 16,
+            layer_width: 8000,
+            beam_width: 250000,
+            max_keep: 60000,
+            reservoir_size: 50000,
+            lookahead_width: 15000,
+            lookahead_depth: 2,
+            lookahead_samples: None,
+            collision_width: 10000,
+            walk_length: 200,
+            max_images: 10000000,
+            count: 1,
+            time_limit: None,
+            output: None,
+            checkpoint: None,
+            checkpoint_interval: 600,
+            resume: None,
+            tt_memory: 0,
+            format: Format::Human,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyOptions {
+    pub p: u32,
+    pub strands: usize,
+    pub factors: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    None,
+    Free,
+    Short,
+}
+
+impl FromStr for Reduction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Reduction, String> {
+        match s {
+            "none" => Ok(Reduction::None),
+            "free" => Ok(Reduction::Free),
+            "short" => Ok(Reduction::Short),
+            _ => Err(format!("unknown reduction '{}'", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordOptions {
+    pub strands: usize,
+    pub delta_power: i32,
+    pub factors: String,
+    pub notation: Notation,
+    pub reduce: Reduction,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NormalFormOptions {
+    pub p: u32,
+    pub strands: usize,
+    pub word: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Search(Box<SearchOptions>),
+    Verify(VerifyOptions),
+    Word(WordOptions),
+    NormalForm(NormalFormOptions),
+    Help,
+}
+
+fn parse_value<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, option))
+}
+
+fn parse_positive(option: &str, value: &str) -> Result<usize, String> {
+    let res: usize = parse_value(option, value)?;
+    if res == 0 {
+        return Err(format!("{} must be positive", option));
+    }
+    Ok(res)
+}
+
+fn parse_p(value: &str) -> Result<u32, String> {
+    let p: u32 = parse_value("<P>", value)?;
+    if !is_prime(p) {
+        return Err(format!("<P> must be a prime, but {} is not", p));
+    }
+    Ok(p)
+}
+
+fn parse_strands(option: &str, value: &str) -> Result<usize, String> {
+    let n: usize = parse_value(option, value)?;
+    if !(MIN_STRANDS..=MAX_STRANDS).contains(&n) {
+        return Err(format!("{} must be between {} and {}", option, MIN_STRANDS, MAX_STRANDS));
+    }
+    Ok(n)
+}
+
+/// Parses the command line, without the program name.
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let subcommand = match args.first().map(|x| x.as_str()) {
+        Some(x @ ("verify" | "word" | "normal-form")) => Some(x),
+        _ => None,
+    };
+    let args = if subcommand.is_some() { &args[1..] } else { args };
+
+    let mut positional: Vec<&str> = Vec::new();
+    let mut options: Vec<(&str, &str)> = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        i += 1;
+        if arg == "-h" || arg == "--help" {
+            return Ok(Command::Help);
+        }
+        // Words in the Artin generators may start with a negative number.
+        if !arg.starts_with('-') || arg.len() == 1 || arg[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            positional.push(arg);
+            continue;
+        }
+        if let Some((option, value)) = arg.split_once('=') {
+            options.push((option, value));
+        } else if i < args.len() {
+            options.push((arg, args[i].as_str()));
+            i += 1;
+        } else {
+            return Err(format!("missing value for {}", arg));
+        }
+    }
+
+    if subcommand == Some("verify") {
+        let [p, factors] = positional[..] else {
+            return Err("verify expects <P> and <FACTORS>".to_string());
+        };
+        let mut res = VerifyOptions {
+            p: parse_p(p)?,
+            strands: 4,
+            factors: factors.to_string(),
+        };
+        for (option, value) in options {
+            match option {
+                "-n" | "--strands" => res.strands = parse_strands(option, value)?,
+                _ => return Err(format!("unknown option {} for verify", option)),
+            }
+        }
+        return Ok(Command::Verify(res));
+    }
+
+    if subcommand == Some("word") {
+        let [factors] = positional[..] else {
+            return Err("word expects <FACTORS>".to_string());
+        };
+        let mut res = WordOptions {
+            strands: 4,
+            delta_power: 0,
+            factors: factors.to_string(),
+            notation: Notation::Numbers,
+            reduce: Reduction::None,
+        };
+        for (option, value) in options {
+            match option {
+                "-n" | "--strands" => res.strands = parse_strands(option, value)?,
+                "--delta-power" => res.delta_power = parse_value(option, value)?,
+                "--notation" => res.notation = value.parse()?,
+                "--reduce" => res.reduce = value.parse()?,
+                _ => return Err(format!("unknown option {} for word", option)),
+            }
+        }
+        return Ok(Command::Word(res));
+    }
+
+    if subcommand == Some("normal-form") {
+        let [p, word] = positional[..] else {
+            return Err("normal-form expects <P> and <WORD>".to_string());
+        };
+        let mut res = NormalFormOptions {
+            p: if p == "0" { 0 } else { parse_p(p)? },
+            strands: 4,
+            word: word.to_string(),
+        };
+        for (option, value) in options {
+            match option {
+                "-n" | "--strands" => res.strands = parse_strands(option, value)?,
+                _ => return Err(format!("unknown option {} for normal-form", option)),
+            }
+        }
+        return Ok(Command::NormalForm(res));
+    }
+
+    let [p] = positional[..] else {
+        return Err("expected exactly one positional argument <P>".to_string());
+    };
+    let mut res = SearchOptions::new(parse_p(p)?);
+    for (option, value) in options {
+        match option {
+            "-s" | "--strategy" => res.strategy = value.parse()?,
+            "-n" | "--strands" => res.strands = parse_strands(option, value)?,
+            "--seed" => res.seed = parse_value(option, value)?,
+            "--keep" => res.keep = parse_positive(option, value)?,
+            "--handle" => res.handle = parse_positive(option, value)?,
+            "--chunk-size" => res.chunk_size = parse_positive(option, value)?,
+            "--layer-width" => res.layer_width = parse_positive(option, value)?,
+            "--beam-width" => res.beam_width = parse_positive(option, value)?,
+            "--max-keep" => res.max_keep = parse_positive(option, value)?,
+            "--reservoir-size" => res.reservoir_size = parse_positive(option, value)?,
+            "--lookahead-width" => res.lookahead_width = parse_positive(option, value)?,
+            "--lookahead-depth" => res.lookahead_depth = parse_positive(option, value)?,
+            "--lookahead-samples" => res.lookahead_samples = Some(parse_positive(option, value)?),
+            "--collision-width" => res.collision_width = parse_positive(option, value)?,
+            "--walk-length" => res.walk_length = parse_positive(option, value)?,
+            "--max-images" => res.max_images = parse_positive(option, value)?,
+            "--count" => res.count = parse_positive(option, value)?,
+            "--time-limit" => res.time_limit = Some(parse_value(option, value)?),
+            "--output" => res.output = Some(PathBuf::from(value)),
+            "--checkpoint" => res.checkpoint = Some(PathBuf::from(value)),
+            "--checkpoint-interval" => res.checkpoint_interval = parse_value(option, value)?,
+            "--resume" => res.resume = Some(PathBuf::from(value)),
+            "--tt-memory" => res.tt_memory = parse_value(option, value)?,
+            "--format" => res.format = value.parse()?,
+            _ => return Err(format!("unknown option {}", option)),
+        }
+    }
+    if (res.checkpoint.is_some() || res.resume.is_some()) && !res.strategy.supports_checkpoints() {
+        return Err(format!("strategy {} does not support checkpoints", res.strategy));
+    }
+    if res.tt_memory > 0 && !res.strategy.supports_transpositions() {
+        return Err(format!("strategy {} does not use a transposition table", res.strategy));
+    }
+    if res.tt_memory.checked_mul(1 << 20).is_none() {
+        return Err(format!("--tt-memory {} MiB is too large", res.tt_memory));
+    }
+    Ok(Command::Search(Box::new(res)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_defaults() {
+        assert_eq!(parse(&args("5")), Ok(Command::Search(Box::new(SearchOptions::new(5)))));
+    }
+
+    #[test]
+    fn parse_strategy_and_tunables() {
+        let Ok(Command::Search(opts)) = parse(&args("-s reservoir 3 --seed=7 --reservoir-size 10")) else {
+            panic!("expected search options");
+        };
+        assert_eq!(opts.strategy, Strategy::Reservoir);
+        assert_eq!(opts.p, 3);
+        assert_eq!(opts.seed, 7);
+        assert_eq!(opts.reservoir_size, 10);
+        assert_eq!(opts.tt_memory, 0);
+    }
+
+    #[test]
+    fn parse_transposition_table_memory() {
+        let Ok(Command::Search(opts)) = parse(&args("3 --tt-memory 512")) else {
+            panic!("expected search options");
+        };
+        assert_eq!(opts.tt_memory, 512);
+        assert!(parse(&args("3 --tt-memory -1")).is_err());
+        assert!(parse(&args(&format!("3 --tt-memory {}", usize::MAX >> 10))).is_err());
+        assert!(parse(&args("3 -s collision --tt-memory 512")).is_err());
+        assert!(parse(&args("3 -s birthday --tt-memory 0")).is_ok());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_values() {
+        assert!(parse(&args("3 --keep 0")).is_err());
+        assert!(parse(&args("3 --strands 2")).is_err());
+        assert!(parse(&args("3 --strategy dfs")).is_err());
+        assert!(parse(&args("3 --seed")).is_err());
+        assert!(parse(&args("1")).is_err());
+        assert!(parse(&args("9")).is_err());
+        assert!(parse(&args("256")).is_err());
+        assert!(parse(&args("3 4")).is_err());
+        assert!(parse(&args("3 --unknown 1")).is_err());
+    }
+
+    #[test]
+    fn parse_checkpoints() {
+        let Ok(Command::Search(opts)) = parse(&args("3 --resume run.ckp --checkpoint-interval 60")) else {
+            panic!("expected search options");
+        };
+        assert_eq!(opts.resume, Some(PathBuf::from("run.ckp")));
+        assert_eq!(opts.checkpoint, None);
+        assert_eq!(opts.checkpoint_interval, 60);
+        assert!(parse(&args("3 -s reservoir --checkpoint run.ckp")).is_err());
+    }
+
+    #[test]
+    fn parse_collection() {
+        let Ok(Command::Search(opts)) = parse(&args("3 --count 10 --time-limit 3600 --output found.csv")) else {
+            panic!("expected search options");
+        };
+        assert_eq!(opts.count, 10);
+        assert_eq!(opts.time_limit, Some(3600));
+        assert_eq!(opts.output, Some(PathBuf::from("found.csv")));
+        assert!(parse(&args("3 --count 0")).is_err());
+    }
+
+    #[test]
+    fn parse_format() {
+        let Ok(Command::Search(opts)) = parse(&args("3 --format json")) else {
+            panic!("expected search options");
+        };
+        assert_eq!(opts.format, Format::Json);
+        assert!(parse(&args("3 --format xml")).is_err());
+    }
+
+    #[test]
+    fn parse_verify() {
+        let command = parse(&args("verify -n 5 2 13,10"));
+        let expected = VerifyOptions {
+            p: 2,
+            strands: 5,
+            factors: "13,10".to_string(),
+        };
+        assert_eq!(command, Ok(Command::Verify(expected)));
+        assert_eq!(parse(&args("3 --help")), Ok(Command::Help));
+    }
+
+    #[test]
+    fn parse_word() {
+        let command = parse(&args("word --delta-power -4 --notation letters --reduce short 10,13"));
+        let expected = WordOptions {
+            strands: 4,
+            delta_power: -4,
+            factors: "10,13".to_string(),
+            notation: Notation::Letters,
+            reduce: Reduction::Short,
+        };
+        assert_eq!(command, Ok(Command::Word(expected)));
+        assert!(parse(&args("word --reduce all 10")).is_err());
+        assert!(parse(&args("word 3 10")).is_err());
+    }
+
+    #[test]
+    fn parse_normal_form() {
+        let command = parse(&["normal-form", "5", "-1 2 -3", "-n", "5"].map(String::from));
+        let expected = NormalFormOptions {
+            p: 5,
+            strands: 5,
+            word: "-1 2 -3".to_string(),
+        };
+        assert_eq!(command, Ok(Command::NormalForm(expected)));
+        assert!(parse(&args("normal-form 5")).is_err());
+        assert!(matches!(parse(&args("normal-form 0 1")), Ok(Command::NormalForm(NormalFormOptions { p: 0, .. }))));
+        assert!(parse(&args("normal-form 4 1")).is_err());
+    }
+
+    #[test]
+    fn strategy_names_round_trip() {
+        for name in ["best-first-parallel", "beam-parallel", "beam", "best-first-limited", "reservoir", "lookahead", "collision", "birthday"] {
+            let strategy: Strategy = name.parse().unwrap();
+            assert_eq!(strategy.to_string(), name);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Collision searches for pairs of braids with the same projective Burau
+//! image. If a and b are distinct braids whose images agree up to a scalar,
+//! then a·b⁻¹ is a kernel element, so instead of driving a single braid to
+//! projlen 1, these searches store the images they reach and wait for two of
+//! them to coincide. Like the other searches, they keep going until the
+//! [`Findings`] have enough kernel elements or run out of time.
+
+use hashbrown::HashMap;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+
+use crate::findings::Findings;
+use crate::garside::{generate_descendants, quotient, ActionTable};
+use crate::progress;
+use crate::report::Event;
+use crate::queue::TieBreak;
+use crate::search::{bucket_by_projlen, report_exhausted, Frontier, State};
+use crate::transposition::fingerprint;
+use crate::tree::Path;
+
+/// Two distinct braids with the same projective Burau image, together with
+/// the left normal form Δ^delta_power·factors of the kernel element a·b⁻¹.
+pub struct Collision {
+    pub a: State,
+    pub b: State,
+    pub delta_power: i32,
+    pub factors: Vec<u32>,
+}
+
+/// The braids reached so far, by the fingerprint of their projective image.
+struct ImageStore {
+    images: HashMap<u64, Path>,
+    capacity: usize,
+}
+
+impl ImageStore {
+    fn new(capacity: usize) -> ImageStore {
+        ImageStore {
+            images: HashMap::new(),
+            capacity,
+        }
+    }
+
+    /// Records the image of `state`, and returns a collision if a different
+    /// braid with the same projective image has been recorded before. Once
+    /// the store is full, images are only looked up.
+    fn record(&mut self, state: &State, table: &ActionTable) -> Option<Collision> {
+        let key = fingerprint(&state.mat);
+        let Some(other) = self.images.get(&key) else {
+            if self.images.len() < self.capacity {
+                self.images.insert(key, state.factors.clone());
+            }
+            return None;
+        };
+        if *other == state.factors {
+            return None;
+        }
+        // Rule out a collision of fingerprints rather than of images.
+        let other = State::from_factors(&other.to_vec(), table, state.mat.p);
+        if other.mat.canonical() != state.mat.canonical() {
+            return None;
+        }
+        let (delta_power, factors) = quotient(table.n, &state.factors.to_vec(), &other.factors.to_vec());
+        // Distinct braids without powers of Δ never differ by a central
+        // element, but a trivial quotient would not be worth reporting.
+        if factors.is_empty() {
+            return None;
+        }
+        Some(Collision {
+            a: state.clone(),
+            b: other,
+            delta_power,
+            factors,
+        })
+    }
+}
+
+/// Prints a collision found by one of the searches.
+pub fn report_collision(collision: &Collision) {
+    progress!("Found two braids with the same projective Burau image. Garside generators:");
+    progress!("{:?}", collision.a.factors);
+    progress!("{:?}", collision.b.factors);
+    progress!(
+        "Kernel element a·b⁻¹ in left normal form Δ^k·A1⋯Ar, with k = {}:",
+        collision.delta_power
+    );
+    progress!("{:?}", collision.factors);
+    Event::new("collision")
+        .list("a", &collision.a.factors.to_vec())
+        .list("b", &collision.b.factors.to_vec())
+        .emit();
+}
+
+/// Grows two frontiers layer by layer, keeping the `layer_width` states of
+/// lowest projlen in each, and breaking ties at random with independent
+/// random streams. The images of all states reached by either frontier are
+/// stored, up to `max_images` of them, and the search stops once a new state
+/// has the same projective image as a stored one. Returns the collisions
+/// found.
+pub fn collision_search(
+    states: Frontier,
+    seed: u64,
+    layer_width: usize,
+    max_images: usize,
+    findings: &mut Findings,
+    n: usize,
+) -> Vec<Collision> {
+    let descendants = generate_descendants(n);
+    let table = ActionTable::new(n);
+    let mut store = ImageStore::new(max_images);
+    let mut collisions = Vec::new();
+    let initial: Vec<State> = states.into_values().collect();
+    for state in &initial {
+        if let Some(collision) = store.record(state, &table) {
+            report_collision(&collision);
+            findings.record(&collision.factors, collision.delta_power, Some(0));
+            collisions.push(collision);
+        }
+    }
+    let mut rngs = [Pcg32::seed_from_u64(seed), Pcg32::seed_from_u64(seed.wrapping_add(1))];
+    let mut frontiers = [bucket_by_projlen(initial.clone()), bucket_by_projlen(initial)];
+    let mut layer = 1;
+    while !findings.is_done() {
+        if frontiers.iter().all(|x| x.is_empty()) {
+            report_exhausted();
+            break;
+        }
+        for (frontier, rng) in frontiers.iter_mut().zip(&mut rngs) {
+            let mut next = Frontier::new(layer_width, TieBreak::Random(Pcg32::seed_from_u64(rng.gen())));
+            for state in frontier.values() {
+                let last_factor = state.factors.last();
+                for descendant in &descendants[&last_factor] {
+                    let new_state = state.append(*descendant, &table);
+                    if let Some(collision) = store.record(&new_state, &table) {
+                        report_collision(&collision);
+                        let done = findings.record(&collision.factors, collision.delta_power, Some(layer));
+                        collisions.push(collision);
+                        if done {
+                            return collisions;
+                        }
+                    }
+                    next.push(new_state.projlen(), new_state);
+                }
+            }
+            *frontier = next;
+        }
+        progress!(
+            "Finished layer {}. Lowest projlens: {}, {}. Stored images: {}",
+            layer,
+            frontiers[0].min_key().unwrap_or(0),
+            frontiers[1].min_key().unwrap_or(0),
+            store.images.len()
+        );
+        Event::new("layer")
+            .field("layer", layer)
+            .list(
+                "lowest_projlens",
+                &frontiers.each_ref().map(|x| x.min_key().unwrap_or(0)),
+            )
+            .field("stored_images", store.images.len())
+            .float("elapsed_secs", findings.elapsed().as_secs_f64())
+            .emit();
+        layer += 1;
+    }
+    collisions
+}
+
+/// Birthday search by random walks of `walk_length` factors, each starting
+/// at a random factor and continuing with a random factor among those giving
+/// the lowest projlen. The images of all states visited by the walks are
+/// stored, up to `max_images` of them, and the search stops once a walk
+/// visits a projective image stored by an earlier one. Returns the
+/// collisions found.
+pub fn birthday_search(
+    states: Frontier,
+    seed: u64,
+    walk_length: usize,
+    max_images: usize,
+    findings: &mut Findings,
+    n: usize,
+) -> Vec<Collision> {
+    let descendants = generate_descendants(n);
+    let table = ActionTable::new(n);
+    let mut rng = Pcg32::seed_from_u64(seed);
+    let mut store = ImageStore::new(max_images);
+    let mut collisions = Vec::new();
+    let initial: Vec<State> = states.into_values().collect();
+    if initial.is_empty() {
+        report_exhausted();
+        return collisions;
+    }
+    let mut walks: u64 = 0;
+    while !findings.is_done() {
+        let mut state = initial[rng.gen_range(0..initial.len())].clone();
+        for _ in 0..walk_length {
+            if let Some(collision) = store.record(&state, &table) {
+                report_collision(&collision);
+                let done = findings.record(&collision.factors, collision.delta_power, None);
+                collisions.push(collision);
+                if done {
+                    return collisions;
+                }
+            }
+            let last_factor = state.factors.last();
+            let next: Vec<State> = descendants[&last_factor]
+                .iter()
+                .map(|x| state.append(*x, &table))
+                .collect();
+            let lowest = next.iter().map(|x| x.projlen()).min().unwrap();
+            let best: Vec<State> = next.into_iter().filter(|x| x.projlen() == lowest).collect();
+            state = best[rng.gen_range(0..best.len())].clone();
+        }
+        walks += 1;
+        if walks.is_power_of_two() {
+            progress!("Finished {} walks. Stored images: {}", walks, store.images.len());
+            Event::new("progress")
+                .field("walks", walks)
+                .field("stored_images", store.images.len())
+                .float("elapsed_secs", findings.elapsed().as_secs_f64())
+                .emit();
+        }
+    }
+    collisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garside::{burau_image, left_normal_form, simple_element};
+    use crate::search::initial_states;
+
+    #[test]
+    fn collision_search_finds_kernel_element_mod_two() {
+        let table = ActionTable::new(4);
+        let mut findings = Findings::new(1, None, 2, 4, 0);
+        let collisions = collision_search(initial_states(&table, 2), 0, 100, 1 << 20, &mut findings, 4);
+        let collision = &collisions[0];
+        assert_ne!(collision.a.factors, collision.b.factors);
+        assert_eq!(collision.a.mat.canonical(), collision.b.mat.canonical());
+        let (k, mut factors) = (collision.delta_power, collision.factors.clone());
+        factors.extend(collision.b.factors.to_vec());
+        let simples: Vec<_> = factors.iter().map(|x| simple_element(4, *x)).collect();
+        assert_eq!(left_normal_form(4, k, &simples), (0, collision.a.factors.to_vec()));
+        assert_eq!(findings.found()[0].factors, collision.factors);
+        // The kernel element has negative infimum, and its image is scalar.
+        assert!(collision.delta_power < 0);
+        let image = burau_image(collision.delta_power, &collision.factors, &table, 2).unwrap();
+        assert!(image.is_scalar());
+    }
+}
@@ -0,0 +1,221 @@
+//! Collecting the kernel elements found by a search. A search keeps going
+//! after its first hit until it has found the requested number of distinct
+//! elements or its time budget has run out, and every new element may be
+//! written to an output file together with how it was found.
+
+use hashbrown::HashSet;
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::garside::{left_normal_form, simple_element};
+use crate::permutation::Permutation;
+use crate::progress;
+use crate::report::Event;
+
+/// Header of the output file, which has one comma-separated line per kernel
+/// element with its factors separated by spaces.
+const HEADER: &str = "p,n,seed,layer,elapsed_secs,length,delta_power,factors";
+
+/// A kernel element Δ^delta_power·factors, in left normal form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Found {
+    pub factors: Vec<u32>,
+    pub delta_power: i32,
+    /// Layer of the search in which the element was found, for strategies
+    /// proceeding in layers.
+    pub layer: Option<u64>,
+    /// Time since the start of the search.
+    pub elapsed: Duration,
+}
+
+/// The distinct kernel elements found so far by a search, and when to stop.
+pub struct Findings {
+    wanted: usize,
+    time_limit: Option<Duration>,
+    start: Instant,
+    p: u32,
+    n: usize,
+    seed: u64,
+    keys: HashSet<Vec<u32>>,
+    found: Vec<Found>,
+    output: Option<BufWriter<std::fs::File>>,
+}
+
+impl Findings {
+    /// Stops after `wanted` distinct kernel elements of B_n mod p, or once
+    /// `time_limit` has passed, whichever comes first.
+    pub fn new(wanted: usize, time_limit: Option<Duration>, p: u32, n: usize, seed: u64) -> Findings {
+        Findings {
+            wanted,
+            time_limit,
+            start: Instant::now(),
+            p,
+            n,
+            seed,
+            keys: HashSet::new(),
+            found: Vec::new(),
+            output: None,
+        }
+    }
+
+    /// Appends every new kernel element to the file at `path`, which is
+    /// created with a header if it does not exist.
+    pub fn write_to(mut self, path: &Path) -> io::Result<Findings> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_empty = file.metadata()?.len() == 0;
+        let mut output = BufWriter::new(file);
+        if is_empty {
+            writeln!(output, "{}", HEADER)?;
+            output.flush()?;
+        }
+        self.output = Some(output);
+        Ok(self)
+    }
+
+    pub fn found(&self) -> &[Found] {
+        &self.found
+    }
+
+    /// Time since the start of the search.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Whether the search should stop, either because enough elements have
+    /// been found or because the time budget has run out.
+    pub fn is_done(&self) -> bool {
+        self.found.len() >= self.wanted || self.time_limit.is_some_and(|x| self.start.elapsed() >= x)
+    }
+
+    /// Whether the kernel element Δ^delta_power·factors is equivalent to one
+    /// found before.
+    pub fn is_known(&self, factors: &[u32], delta_power: i32) -> bool {
+        self.keys.contains(&conjugacy_key(self.n, delta_power, factors))
+    }
+
+    /// Records the kernel element Δ^delta_power·factors unless it is
+    /// equivalent to one found before, and returns whether the search should
+    /// stop.
+    pub fn record(&mut self, factors: &[u32], delta_power: i32, layer: Option<u64>) -> bool {
+        let is_new = self.keys.insert(conjugacy_key(self.n, delta_power, factors));
+        let elapsed = self.start.elapsed();
+        Event::new("found")
+            .list("factors", factors)
+            .field("delta_power", delta_power)
+            .field("length", factors.len())
+            .maybe("layer", layer)
+            .float("elapsed_secs", elapsed.as_secs_f64())
+            .field("new", is_new)
+            .emit();
+        if !is_new {
+            progress!("Skipping kernel element equivalent to one found before.");
+            return self.is_done();
+        }
+        let found = Found {
+            factors: factors.to_vec(),
+            delta_power,
+            layer,
+            elapsed,
+        };
+        if let Some(output) = &mut self.output {
+            let res = writeln!(
+                output,
+                "{},{},{},{},{:.3},{},{},{}",
+                self.p,
+                self.n,
+                self.seed,
+                layer.map_or(String::new(), |x| x.to_string()),
+                found.elapsed.as_secs_f64(),
+                factors.len(),
+                delta_power,
+                factors.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ")
+            )
+            .and_then(|_| output.flush());
+            if let Err(e) = res {
+                eprintln!("warning: could not write kernel element: {}", e);
+            }
+        }
+        self.found.push(found);
+        if self.wanted > 1 {
+            progress!("Collected {} of {} kernel elements.", self.found.len(), self.wanted);
+        }
+        self.is_done()
+    }
+}
+
+/// A key identifying Δ^delta_power·factors up to powers of Δ and cyclic
+/// conjugation: the least left normal form, without its power of Δ, of the
+/// cyclic permutations of the factors and of their conjugates by Δ. A factor
+/// rotated from the front to the end passes Δ^delta_power, which flips it
+/// when the power is odd, as Δ^k·A1·X is conjugate to Δ^k·X·τ^k(A1).
+fn conjugacy_key(n: usize, delta_power: i32, factors: &[u32]) -> Vec<u32> {
+    let simples: Vec<Permutation> = factors.iter().map(|x| simple_element(n, *x)).collect();
+    let odd = delta_power % 2 != 0;
+    let mut best: Option<Vec<u32>> = None;
+    for flipped in [false, true] {
+        for i in 0..simples.len().max(1) {
+            let moved = simples[..i].iter().map(|x| if odd { x.flip() } else { x.clone() });
+            let mut rotated: Vec<Permutation> = simples[i..].iter().cloned().chain(moved).collect();
+            if flipped {
+                rotated = rotated.iter().map(|x| x.flip()).collect();
+            }
+            let (_, key) = left_normal_form(n, delta_power, &rotated);
+            if best.as_ref().is_none_or(|x| key < *x) {
+                best = Some(key);
+            }
+        }
+    }
+    best.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conjugates_are_equivalent() {
+        let mut findings = Findings::new(3, None, 2, 4, 0);
+        assert!(!findings.record(&[10, 13, 10, 13, 10, 13, 10, 13], 0, Some(3)));
+        assert!(findings.is_known(&[13, 10, 13, 10, 13, 10, 13, 10], 0));
+        assert!(!findings.record(&[13, 10, 13, 10, 13, 10, 13, 10], 0, Some(3)));
+        assert!(!findings.record(&[10, 13, 10, 13, 10, 13, 10, 13], -4, None));
+        assert_eq!(findings.found().len(), 1);
+        assert!(!findings.record(&[13, 4, 13, 20], 0, Some(5)));
+        assert!(findings.record(&[17, 19, 10], 0, Some(5)));
+        assert!(findings.is_done());
+    }
+
+    #[test]
+    fn odd_powers_of_delta_flip_rotated_factors() {
+        // Δ^k·A1·X is conjugate to Δ^k·X·τ(A1) for odd k, and here τ(17) = 22.
+        for k in [-3, -1, 1] {
+            let mut findings = Findings::new(2, None, 2, 4, 0);
+            assert!(!findings.record(&[17, 19, 10, 3], k, None));
+            assert!(findings.is_known(&[19, 10, 3, 22], k));
+            assert!(!findings.is_known(&[19, 10, 3, 17], k));
+        }
+    }
+
+    #[test]
+    fn time_limit_ends_search() {
+        let findings = Findings::new(10, Some(Duration::ZERO), 2, 4, 0);
+        assert!(findings.is_done());
+        assert!(!Findings::new(10, None, 2, 4, 0).is_done());
+    }
+
+    #[test]
+    fn output_file_has_header_and_metadata() {
+        let path = std::env::temp_dir().join(format!("burau4_mod_p_{}_findings", std::process::id()));
+        let mut findings = Findings::new(2, None, 2, 4, 7).write_to(&path).unwrap();
+        findings.record(&[10, 13, 10, 13, 10, 13, 10, 13], 0, Some(3));
+        drop(findings);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines[0], HEADER);
+        assert!(lines[1].starts_with("2,4,7,3,"));
+        assert!(lines[1].ends_with(",8,0,10 13 10 13 10 13 10 13"));
+    }
+}
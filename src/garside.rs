@@ -1,442 +1,668 @@
 use hashbrown::HashMap;
+use std::fmt;
 
-use crate::algebra::{Matrix, Polynomial};
+use crate::algebra::{DenseMatrix, LaurentMatrix, Matrix, Span};
+use crate::integral::IntMatrix;
+use crate::permutation::{factorial, Permutation};
 
-pub fn generate_descendants() -> HashMap<u32, Vec<u32>> {
+/// The non-trivial proper simple elements of B_n, that is, the positive
+/// permutation braids other than the identity and Δ. Garside factors are
+/// identified by ids `1..=num_factors(n)`, and the factor with id `k` is the
+/// permutation of lexicographic rank `k`.
+///
+/// For B_4 this gives the following factors, listed with their images and
+/// a positive word in the Artin generators:
+///
+/// | id | images  | word |
+/// |----|---------|------|
+/// |  1 | 0 1 3 2 | σ3 |
+/// |  2 | 0 2 1 3 | σ2 |
+/// |  3 | 0 2 3 1 | σ2 σ3 |
+/// |  4 | 0 3 1 2 | σ3 σ2 |
+/// |  5 | 0 3 2 1 | σ2 σ3 σ2 |
+/// |  6 | 1 0 2 3 | σ1 |
+/// |  7 | 1 0 3 2 | σ3 σ1 |
+/// |  8 | 1 2 0 3 | σ1 σ2 |
+/// |  9 | 1 2 3 0 | σ1 σ2 σ3 |
+/// | 10 | 1 3 0 2 | σ3 σ1 σ2 |
+/// | 11 | 1 3 2 0 | σ1 σ2 σ3 σ2 |
+/// | 12 | 2 0 1 3 | σ2 σ1 |
+/// | 13 | 2 0 3 1 | σ2 σ3 σ1 |
+/// | 14 | 2 1 0 3 | σ1 σ2 σ1 |
+/// | 15 | 2 1 3 0 | σ1 σ2 σ3 σ1 |
+/// | 16 | 2 3 0 1 | σ2 σ3 σ1 σ2 |
+/// | 17 | 2 3 1 0 | σ1 σ2 σ3 σ1 σ2 |
+/// | 18 | 3 0 1 2 | σ3 σ2 σ1 |
+/// | 19 | 3 0 2 1 | σ2 σ3 σ2 σ1 |
+/// | 20 | 3 1 0 2 | σ3 σ1 σ2 σ1 |
+/// | 21 | 3 1 2 0 | σ1 σ2 σ3 σ2 σ1 |
+/// | 22 | 3 2 0 1 | σ2 σ3 σ1 σ2 σ1 |
+pub fn simple_elements(n: usize) -> Vec<Permutation> {
+    let mut perms = Permutation::all(n);
+    perms.pop();
+    perms.remove(0);
+    perms
+}
+
+/// The permutation underlying the factor with id `factor`.
+pub fn simple_element(n: usize, factor: u32) -> Permutation {
+    Permutation::from_rank(n, factor as usize)
+}
+
+/// Range of the supported numbers of strands; B_8 already has 40318 factors,
+/// which no longer fit in the search tree.
+pub const MIN_STRANDS: usize = 3;
+pub const MAX_STRANDS: usize = 7;
+
+pub fn num_factors(n: usize) -> u32 {
+    (factorial(n) - 2) as u32
+}
+
+/// For every factor, the factors that may follow it in left normal form:
+/// the starting set of the next factor must be contained in the finishing
+/// set of the previous one.
+pub fn generate_descendants(n: usize) -> HashMap<u32, Vec<u32>> {
+    let simples = simple_elements(n);
+    let starting: Vec<u32> = simples.iter().map(|x| x.left_descents()).collect();
     let mut d = HashMap::new();
-    d.insert(1, vec![1, 4, 18]);
-    d.insert(2, vec![2, 3, 12, 13, 16]);
-    d.insert(3, vec![1, 4, 18]);
-    d.insert(4, vec![2, 3, 12, 13, 16]);
-    d.insert(5, vec![1, 2, 3, 4, 5, 12, 13, 16, 18, 19, 22]);
-    d.insert(6, vec![6, 8, 9]);
-    d.insert(7, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]);
-    d.insert(8, vec![2, 3, 12, 13, 16]);
-    d.insert(9, vec![1, 4, 18]);
-    d.insert(10, vec![2, 3, 12, 13, 16]);
-    d.insert(11, vec![1, 2, 3, 4, 5, 12, 13, 16, 18, 19, 22]);
-    d.insert(12, vec![6, 8, 9]);
-    d.insert(13, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]);
-    d.insert(14, vec![2, 3, 6, 8, 9, 12, 13, 14, 15, 16, 17]);
-    d.insert(15, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]);
-    d.insert(16, vec![2, 3, 12, 13, 16]);
-    d.insert(17, vec![1, 2, 3, 4, 5, 12, 13, 16, 18, 19, 22]);
-    d.insert(18, vec![6, 8, 9]);
-    d.insert(19, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]);
-    d.insert(20, vec![2, 3, 6, 8, 9, 12, 13, 14, 15, 16, 17]);
-    d.insert(21, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]);
-    d.insert(22, vec![2, 3, 6, 8, 9, 12, 13, 14, 15, 16, 17]);
+    for (i, a) in simples.iter().enumerate() {
+        let finishing = a.right_descents();
+        let descendants = starting
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| *s & !finishing == 0)
+            .map(|(j, _)| j as u32 + 1)
+            .collect();
+        d.insert(i as u32 + 1, descendants);
+    }
     d
 }
 
-pub fn act_by(mat: &Matrix, element: u32, p: u8) -> Matrix {
-    let d = match element {
-        1 => [
-            mat.d[0].mult(0, false),
-            mat.d[1].mult(0, false) + mat.d[2].mult(1, true),
-            mat.d[2].mult(2, true),
-            mat.d[3].mult(0, false),
-            mat.d[4].mult(0, false) + mat.d[5].mult(1, true),
-            mat.d[5].mult(2, true),
-            mat.d[6].mult(0, false),
-            mat.d[7].mult(0, false) + mat.d[8].mult(1, true),
-            mat.d[8].mult(2, true),
-        ],
-        2 => [
-            mat.d[0].mult(0, false) + mat.d[1].mult(1, true),
-            mat.d[1].mult(2, true),
-            mat.d[1].mult(1, true) + mat.d[2].mult(0, false),
-            mat.d[3].mult(0, false) + mat.d[4].mult(1, true),
-            mat.d[4].mult(2, true),
-            mat.d[4].mult(1, true) + mat.d[5].mult(0, false),
-            mat.d[6].mult(0, false) + mat.d[7].mult(1, true),
-            mat.d[7].mult(2, true),
-            mat.d[7].mult(1, true) + mat.d[8].mult(0, false),
-        ],
-        3 => [
-            mat.d[0].mult(0, false) + mat.d[1].mult(1, true),
-            mat.d[2].mult(1, true),
-            mat.d[1].mult(3, false) + mat.d[2].mult(2, true),
-            mat.d[3].mult(0, false) + mat.d[4].mult(1, true),
-            mat.d[5].mult(1, true),
-            mat.d[4].mult(3, false) + mat.d[5].mult(2, true),
-            mat.d[6].mult(0, false) + mat.d[7].mult(1, true),
-            mat.d[8].mult(1, true),
-            mat.d[7].mult(3, false) + mat.d[8].mult(2, true),
-        ],
-        4 => [
-            mat.d[0].mult(0, false) + mat.d[1].mult(1, true) + mat.d[2].mult(2, false),
-            mat.d[1].mult(2, true) + mat.d[2].mult(3, false),
-            mat.d[1].mult(1, true),
-            mat.d[3].mult(0, false) + mat.d[4].mult(1, true) + mat.d[5].mult(2, false),
-            mat.d[4].mult(2, true) + mat.d[5].mult(3, false),
-            mat.d[4].mult(1, true),
-            mat.d[6].mult(0, false) + mat.d[7].mult(1, true) + mat.d[8].mult(2, false),
-            mat.d[7].mult(2, true) + mat.d[8].mult(3, false),
-            mat.d[7].mult(1, true),
-        ],
-        5 => [
-            mat.d[0].mult(0, false) + mat.d[1].mult(1, true) + mat.d[2].mult(2, false),
-            mat.d[2].mult(3, false),
-            mat.d[1].mult(3, false),
-            mat.d[3].mult(0, false) + mat.d[4].mult(1, true) + mat.d[5].mult(2, false),
-            mat.d[5].mult(3, false),
-            mat.d[4].mult(3, false),
-            mat.d[6].mult(0, false) + mat.d[7].mult(1, true) + mat.d[8].mult(2, false),
-            mat.d[8].mult(3, false),
-            mat.d[7].mult(3, false),
-        ],
-        6 => [
-            mat.d[0].mult(2, true),
-            mat.d[0].mult(1, true) + mat.d[1].mult(0, false),
-            mat.d[2].mult(0, false),
-            mat.d[3].mult(2, true),
-            mat.d[3].mult(1, true) + mat.d[4].mult(0, false),
-            mat.d[5].mult(0, false),
-            mat.d[6].mult(2, true),
-            mat.d[6].mult(1, true) + mat.d[7].mult(0, false),
-            mat.d[8].mult(0, false),
-        ],
-        7 => [
-            mat.d[0].mult(2, true),
-            mat.d[0].mult(1, true) + mat.d[1].mult(0, false) + mat.d[2].mult(1, true),
-            mat.d[2].mult(2, true),
-            mat.d[3].mult(2, true),
-            mat.d[3].mult(1, true) + mat.d[4].mult(0, false) + mat.d[5].mult(1, true),
-            mat.d[5].mult(2, true),
-            mat.d[6].mult(2, true),
-            mat.d[6].mult(1, true) + mat.d[7].mult(0, false) + mat.d[8].mult(1, true),
-            mat.d[8].mult(2, true),
-        ],
-        8 => [
-            mat.d[1].mult(1, true),
-            mat.d[0].mult(3, false) + mat.d[1].mult(2, true),
-            mat.d[0].mult(2, false) + mat.d[1].mult(1, true) + mat.d[2].mult(0, false),
-            mat.d[4].mult(1, true),
-            mat.d[3].mult(3, false) + mat.d[4].mult(2, true),
-            mat.d[3].mult(2, false) + mat.d[4].mult(1, true) + mat.d[5].mult(0, false),
-            mat.d[7].mult(1, true),
-            mat.d[6].mult(3, false) + mat.d[7].mult(2, true),
-            mat.d[6].mult(2, false) + mat.d[7].mult(1, true) + mat.d[8].mult(0, false),
-        ],
-        9 => [
-            mat.d[1].mult(1, true),
-            mat.d[2].mult(1, true),
-            mat.d[0].mult(4, true) + mat.d[1].mult(3, false) + mat.d[2].mult(2, true),
-            mat.d[4].mult(1, true),
-            mat.d[5].mult(1, true),
-            mat.d[3].mult(4, true) + mat.d[4].mult(3, false) + mat.d[5].mult(2, true),
-            mat.d[7].mult(1, true),
-            mat.d[8].mult(1, true),
-            mat.d[6].mult(4, true) + mat.d[7].mult(3, false) + mat.d[8].mult(2, true),
-        ],
-        10 => [
-            mat.d[1].mult(1, true) + mat.d[2].mult(2, false),
-            mat.d[0].mult(3, false) + mat.d[1].mult(2, true) + mat.d[2].mult(3, false),
-            mat.d[0].mult(2, false) + mat.d[1].mult(1, true),
-            mat.d[4].mult(1, true) + mat.d[5].mult(2, false),
-            mat.d[3].mult(3, false) + mat.d[4].mult(2, true) + mat.d[5].mult(3, false),
-            mat.d[3].mult(2, false) + mat.d[4].mult(1, true),
-            mat.d[7].mult(1, true) + mat.d[8].mult(2, false),
-            mat.d[6].mult(3, false) + mat.d[7].mult(2, true) + mat.d[8].mult(3, false),
-            mat.d[6].mult(2, false) + mat.d[7].mult(1, true),
-        ],
-        11 => [
-            mat.d[1].mult(1, true) + mat.d[2].mult(2, false),
-            mat.d[2].mult(3, false),
-            mat.d[0].mult(4, true) + mat.d[1].mult(3, false),
-            mat.d[4].mult(1, true) + mat.d[5].mult(2, false),
-            mat.d[5].mult(3, false),
-            mat.d[3].mult(4, true) + mat.d[4].mult(3, false),
-            mat.d[7].mult(1, true) + mat.d[8].mult(2, false),
-            mat.d[8].mult(3, false),
-            mat.d[6].mult(4, true) + mat.d[7].mult(3, false),
-        ],
-        12 => [
-            mat.d[0].mult(2, true) + mat.d[1].mult(3, false),
-            mat.d[0].mult(1, true),
-            mat.d[1].mult(1, true) + mat.d[2].mult(0, false),
-            mat.d[3].mult(2, true) + mat.d[4].mult(3, false),
-            mat.d[3].mult(1, true),
-            mat.d[4].mult(1, true) + mat.d[5].mult(0, false),
-            mat.d[6].mult(2, true) + mat.d[7].mult(3, false),
-            mat.d[6].mult(1, true),
-            mat.d[7].mult(1, true) + mat.d[8].mult(0, false),
-        ],
-        13 => [
-            mat.d[0].mult(2, true) + mat.d[1].mult(3, false),
-            mat.d[0].mult(1, true) + mat.d[1].mult(2, false) + mat.d[2].mult(1, true),
-            mat.d[1].mult(3, false) + mat.d[2].mult(2, true),
-            mat.d[3].mult(2, true) + mat.d[4].mult(3, false),
-            mat.d[3].mult(1, true) + mat.d[4].mult(2, false) + mat.d[5].mult(1, true),
-            mat.d[4].mult(3, false) + mat.d[5].mult(2, true),
-            mat.d[6].mult(2, true) + mat.d[7].mult(3, false),
-            mat.d[6].mult(1, true) + mat.d[7].mult(2, false) + mat.d[8].mult(1, true),
-            mat.d[7].mult(3, false) + mat.d[8].mult(2, true),
-        ],
-        14 => [
-            mat.d[1].mult(3, false),
-            mat.d[0].mult(3, false),
-            mat.d[0].mult(2, false) + mat.d[1].mult(1, true) + mat.d[2].mult(0, false),
-            mat.d[4].mult(3, false),
-            mat.d[3].mult(3, false),
-            mat.d[3].mult(2, false) + mat.d[4].mult(1, true) + mat.d[5].mult(0, false),
-            mat.d[7].mult(3, false),
-            mat.d[6].mult(3, false),
-            mat.d[6].mult(2, false) + mat.d[7].mult(1, true) + mat.d[8].mult(0, false),
-        ],
-        15 => [
-            mat.d[1].mult(3, false),
-            mat.d[1].mult(2, false) + mat.d[2].mult(1, true),
-            mat.d[0].mult(4, true) + mat.d[1].mult(3, false) + mat.d[2].mult(2, true),
-            mat.d[4].mult(3, false),
-            mat.d[4].mult(2, false) + mat.d[5].mult(1, true),
-            mat.d[3].mult(4, true) + mat.d[4].mult(3, false) + mat.d[5].mult(2, true),
-            mat.d[7].mult(3, false),
-            mat.d[7].mult(2, false) + mat.d[8].mult(1, true),
-            mat.d[6].mult(4, true) + mat.d[7].mult(3, false) + mat.d[8].mult(2, true),
-        ],
-        16 => [
-            mat.d[2].mult(2, false),
-            mat.d[0].mult(3, false) + mat.d[1].mult(4, true) + mat.d[2].mult(3, false),
-            mat.d[0].mult(2, false),
-            mat.d[5].mult(2, false),
-            mat.d[3].mult(3, false) + mat.d[4].mult(4, true) + mat.d[5].mult(3, false),
-            mat.d[3].mult(2, false),
-            mat.d[8].mult(2, false),
-            mat.d[6].mult(3, false) + mat.d[7].mult(4, true) + mat.d[8].mult(3, false),
-            mat.d[6].mult(2, false),
-        ],
-        17 => [
-            mat.d[2].mult(2, false),
-            mat.d[1].mult(4, true) + mat.d[2].mult(3, false),
-            mat.d[0].mult(4, true),
-            mat.d[5].mult(2, false),
-            mat.d[4].mult(4, true) + mat.d[5].mult(3, false),
-            mat.d[3].mult(4, true),
-            mat.d[8].mult(2, false),
-            mat.d[7].mult(4, true) + mat.d[8].mult(3, false),
-            mat.d[6].mult(4, true),
-        ],
-        18 => [
-            mat.d[0].mult(2, true) + mat.d[1].mult(3, false) + mat.d[2].mult(4, true),
-            mat.d[0].mult(1, true),
-            mat.d[1].mult(1, true),
-            mat.d[3].mult(2, true) + mat.d[4].mult(3, false) + mat.d[5].mult(4, true),
-            mat.d[3].mult(1, true),
-            mat.d[4].mult(1, true),
-            mat.d[6].mult(2, true) + mat.d[7].mult(3, false) + mat.d[8].mult(4, true),
-            mat.d[6].mult(1, true),
-            mat.d[7].mult(1, true),
-        ],
-        19 => [
-            mat.d[0].mult(2, true) + mat.d[1].mult(3, false) + mat.d[2].mult(4, true),
-            mat.d[0].mult(1, true) + mat.d[1].mult(2, false),
-            mat.d[1].mult(3, false),
-            mat.d[3].mult(2, true) + mat.d[4].mult(3, false) + mat.d[5].mult(4, true),
-            mat.d[3].mult(1, true) + mat.d[4].mult(2, false),
-            mat.d[4].mult(3, false),
-            mat.d[6].mult(2, true) + mat.d[7].mult(3, false) + mat.d[8].mult(4, true),
-            mat.d[6].mult(1, true) + mat.d[7].mult(2, false),
-            mat.d[7].mult(3, false),
-        ],
-        20 => [
-            mat.d[1].mult(3, false) + mat.d[2].mult(4, true),
-            mat.d[0].mult(3, false),
-            mat.d[0].mult(2, false) + mat.d[1].mult(1, true),
-            mat.d[4].mult(3, false) + mat.d[5].mult(4, true),
-            mat.d[3].mult(3, false),
-            mat.d[3].mult(2, false) + mat.d[4].mult(1, true),
-            mat.d[7].mult(3, false) + mat.d[8].mult(4, true),
-            mat.d[6].mult(3, false),
-            mat.d[6].mult(2, false) + mat.d[7].mult(1, true),
-        ],
-        21 => [
-            mat.d[1].mult(3, false) + mat.d[2].mult(4, true),
-            mat.d[1].mult(2, false),
-            mat.d[0].mult(4, true) + mat.d[1].mult(3, false),
-            mat.d[4].mult(3, false) + mat.d[5].mult(4, true),
-            mat.d[4].mult(2, false),
-            mat.d[3].mult(4, true) + mat.d[4].mult(3, false),
-            mat.d[7].mult(3, false) + mat.d[8].mult(4, true),
-            mat.d[7].mult(2, false),
-            mat.d[6].mult(4, true) + mat.d[7].mult(3, false),
-        ],
-        _ => [
-            mat.d[2].mult(4, true),
-            mat.d[0].mult(3, false) + mat.d[1].mult(4, true),
-            mat.d[0].mult(2, false),
-            mat.d[5].mult(4, true),
-            mat.d[3].mult(3, false) + mat.d[4].mult(4, true),
-            mat.d[3].mult(2, false),
-            mat.d[8].mult(4, true),
-            mat.d[6].mult(3, false) + mat.d[7].mult(4, true),
-            mat.d[6].mult(2, false),
-        ],
-    };
-
-    Matrix { d, p }
+/// Left normal form Δ^k·A1⋯Ar of the braid Δ^delta_power·x1⋯xm, where the
+/// xi are arbitrary simple elements, returned as `k` and the ids of the Ai.
+pub fn left_normal_form(n: usize, delta_power: i32, simples: &[Permutation]) -> (i32, Vec<u32>) {
+    let delta = Permutation::half_twist(n);
+    let mut k = delta_power;
+    let mut factors: Vec<Permutation> = simples.to_vec();
+    loop {
+        let mut changed = false;
+        // Make every adjacent pair left-weighted by moving generators in the
+        // starting set of the second factor but not in the finishing set of
+        // the first one to the first factor.
+        for i in 1..factors.len() {
+            let (left, right) = factors.split_at_mut(i);
+            let (x, y) = (&mut left[i - 1], &mut right[0]);
+            loop {
+                let movable = y.left_descents() & !x.right_descents();
+                if movable == 0 {
+                    break;
+                }
+                let j = movable.trailing_zeros() as usize;
+                x.images.swap(j - 1, j);
+                for value in y.images.iter_mut() {
+                    if *value == j - 1 {
+                        *value = j;
+                    } else if *value == j {
+                        *value = j - 1;
+                    }
+                }
+                changed = true;
+            }
+        }
+        factors.retain(|x| !x.is_identity());
+        // Move factors equal to Δ to the front, using X·Δ = Δ·τ(X).
+        while let Some(i) = factors.iter().position(|x| *x == delta) {
+            factors.remove(i);
+            for x in &mut factors[..i] {
+                *x = x.flip();
+            }
+            k += 1;
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    (k, factors.iter().map(|x| x.rank() as u32).collect())
+}
+
+/// Left normal form of the quotient a·b⁻¹ of two braids given by their
+/// left normal forms without powers of Δ.
+pub fn quotient(n: usize, a: &[u32], b: &[u32]) -> (i32, Vec<u32>) {
+    // Every factor B of b satisfies B⁻¹ = ∂(B)·Δ⁻¹, and Y·Δ⁻¹ = Δ⁻¹·τ(Y), so
+    // b⁻¹ is a product of complements, with the powers of Δ⁻¹ collected on
+    // the left.
+    let mut simples: Vec<Permutation> = a.iter().map(|x| simple_element(n, *x)).collect();
+    for factor in b.iter().rev() {
+        simples.push(simple_element(n, *factor).complement());
+        for x in &mut simples {
+            *x = x.flip();
+        }
+    }
+    left_normal_form(n, -(b.len() as i32), &simples)
+}
+
+/// Right action of the Artin generator σ_i, or of its inverse if `letter`
+/// is -i, on a reduced Burau matrix. The matrix of σ_i agrees with the
+/// identity except in row `i - 1`, which is -v, -v^2, -v around the
+/// diagonal, and that of σ_i⁻¹ has -v^-1, -v^-2, -v^-1 there instead.
+fn act_by_generator(mat: &Matrix, letter: i32) -> Matrix {
+    let dim = mat.dim;
+    let c = letter.unsigned_abs() as usize - 1;
+    let sign = letter.signum();
+    let mut res = mat.clone();
+    for row in 0..dim {
+        let x = mat.get(row, c);
+        if c > 0 {
+            res.d[row * dim + c - 1] = mat.get(row, c - 1) + &x.mult(sign, true);
+        }
+        if c + 1 < dim {
+            res.d[row * dim + c + 1] = mat.get(row, c + 1) + &x.mult(sign, true);
+        }
+        res.d[row * dim + c] = x.mult(2 * sign, true);
+    }
+    res
+}
+
+/// Prime used when expanding the Burau matrices of the simple elements and
+/// their inverses. Their entries are all of the form ±v^k, so any odd prime
+/// will do.
+const TABLE_PRIME: u32 = 127;
+
+/// One term of the right action of a simple element: column `dst` of the
+/// result receives column `src` of the input, multiplied by ±v^power.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Term {
+    pub src: usize,
+    pub dst: usize,
+    pub power: i32,
+    pub neg: bool,
+}
+
+/// The terms of the right action of the product of the Artin generators, or
+/// their inverses, in `word`.
+fn word_terms(dim: usize, word: impl IntoIterator<Item = i32>) -> Vec<Term> {
+    let mut mat = Matrix::identity(dim, TABLE_PRIME);
+    for letter in word {
+        mat = act_by_generator(&mat, letter);
+    }
+    let mut terms = Vec::new();
+    for dst in 0..dim {
+        for src in 0..dim {
+            let entry = mat.get(src, dst);
+            for (power, coef) in entry.powers.iter().zip(&entry.coefs) {
+                assert!(*coef == 1 || *coef == TABLE_PRIME - 1);
+                terms.push(Term {
+                    src,
+                    dst,
+                    power: *power,
+                    neg: *coef != 1,
+                });
+            }
+        }
+    }
+    terms
+}
+
+/// The right action of every factor of B_n, and of its inverse, as a sparse
+/// linear map on the columns of a reduced Burau matrix.
+#[derive(Clone, Debug)]
+pub struct ActionTable {
+    pub n: usize,
+    terms: Vec<Vec<Term>>,
+    inverse_terms: Vec<Vec<Term>>,
+}
+
+impl ActionTable {
+    /// Expands the Burau matrix of every factor as the product of the
+    /// matrices of the Artin generators in its positive word, and that of its
+    /// inverse as the product of their inverses in reverse order.
+    pub fn new(n: usize) -> ActionTable {
+        let dim = n - 1;
+        let mut terms = Vec::new();
+        let mut inverse_terms = Vec::new();
+        for perm in simple_elements(n) {
+            let word: Vec<i32> = perm.positive_word().into_iter().map(|x| x as i32).collect();
+            terms.push(word_terms(dim, word.iter().copied()));
+            inverse_terms.push(word_terms(dim, word.iter().rev().map(|x| -x)));
+        }
+        ActionTable {
+            n,
+            terms,
+            inverse_terms,
+        }
+    }
+
+    pub fn terms(&self, element: u32) -> Result<&[Term], UnknownFactor> {
+        if element == 0 || element as usize > self.terms.len() {
+            return Err(UnknownFactor { factor: element, n: self.n });
+        }
+        Ok(&self.terms[element as usize - 1])
+    }
+
+    /// The terms of the right action of the inverse of the factor.
+    pub fn inverse_terms(&self, element: u32) -> Result<&[Term], UnknownFactor> {
+        self.terms(element)?;
+        Ok(&self.inverse_terms[element as usize - 1])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownFactor {
+    pub factor: u32,
+    pub n: usize,
+}
+
+impl fmt::Display for UnknownFactor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} is not a Garside factor of B_{}; ids run from 1 to {}",
+            self.factor,
+            self.n,
+            num_factors(self.n)
+        )
+    }
+}
+
+impl std::error::Error for UnknownFactor {}
+
+/// Right action of the factor `element` on `mat`, i.e. `mat` multiplied on
+/// the right by the Burau matrix of the factor.
+pub fn act_by<M: LaurentMatrix>(mat: &M, element: u32, table: &ActionTable) -> Result<M, UnknownFactor> {
+    Ok(apply_terms(mat, table.terms(element)?))
 }
 
-pub fn generate_matrix_map(p: u8) -> HashMap<u32, Matrix> {
+/// Right action of the inverse of the factor `element` on `mat`, i.e. `mat`
+/// multiplied on the right by the inverse of the Burau matrix of the factor.
+pub fn act_by_inverse<M: LaurentMatrix>(mat: &M, element: u32, table: &ActionTable) -> Result<M, UnknownFactor> {
+    Ok(apply_terms(mat, table.inverse_terms(element)?))
+}
+
+fn apply_terms<M: LaurentMatrix>(mat: &M, terms: &[Term]) -> M {
+    let mut res = mat.zero_like();
+    for term in terms {
+        res.add_column(term.dst, mat, term.src, term.power, term.neg);
+    }
+    res
+}
+
+/// Right action of the factor `element` on the dense matrix `mat`, written to
+/// `out`, returning the span of powers of the result. Reusing `out` across
+/// calls avoids allocating for the result.
+pub fn act_by_dense(
+    mat: &DenseMatrix,
+    element: u32,
+    table: &ActionTable,
+    out: &mut DenseMatrix,
+) -> Result<Span, UnknownFactor> {
+    let terms = table.terms(element)?;
+    let low = terms.iter().map(|x| x.power).min().unwrap();
+    let high = terms.iter().map(|x| x.power).max().unwrap();
+    let dim = mat.dim;
+    if out.dim != dim || out.p != mat.p {
+        *out = DenseMatrix::zero(dim, mat.p);
+    }
+    out.reset(mat.base + low, mat.width + (high - low) as usize);
+    for term in terms {
+        for row in 0..dim {
+            out.add_shifted(row, term.dst, mat, term.src, (term.power - low) as usize, term.neg);
+        }
+    }
+    Ok(out.trim())
+}
+
+/// Right action of Δ^delta_power·A1⋯Ar on `mat` for arbitrary factors Ai,
+/// where negative powers of Δ act by the inverses of the two factors whose
+/// product is Δ.
+fn act_by_normal_form<M: LaurentMatrix>(
+    mut mat: M,
+    delta_power: i32,
+    factors: &[u32],
+    table: &ActionTable,
+) -> Result<M, UnknownFactor> {
+    // Δ is the product of any factor and its complement.
+    let complement = simple_element(table.n, 1).complement().rank() as u32;
+    for _ in 0..delta_power.unsigned_abs() {
+        mat = if delta_power > 0 {
+            act_by(&act_by(&mat, 1, table)?, complement, table)?
+        } else {
+            act_by_inverse(&act_by_inverse(&mat, complement, table)?, 1, table)?
+        };
+    }
+    for factor in factors {
+        mat = act_by(&mat, *factor, table)?;
+    }
+    Ok(mat)
+}
+
+/// Reduced Burau image mod p of Δ^delta_power·A1⋯Ar for arbitrary factors
+/// Ai.
+pub fn burau_image(delta_power: i32, factors: &[u32], table: &ActionTable, p: u32) -> Result<Matrix, UnknownFactor> {
+    act_by_normal_form(Matrix::identity(table.n - 1, p), delta_power, factors, table)
+}
+
+/// Reduced Burau image over Z[v, v⁻¹] of Δ^delta_power·A1⋯Ar for arbitrary
+/// factors Ai.
+pub fn integral_burau_image(delta_power: i32, factors: &[u32], table: &ActionTable) -> Result<IntMatrix, UnknownFactor> {
+    act_by_normal_form(IntMatrix::identity(table.n - 1), delta_power, factors, table)
+}
+
+pub fn generate_matrix_map(n: usize, p: u32) -> HashMap<u32, Matrix> {
+    let table = ActionTable::new(n);
+    let eye = Matrix::identity(n - 1, p);
     let mut d = HashMap::new();
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(0, 1)], p);
-    mat.d[4] = Polynomial::new(vec![(0, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(2, p - 1)], p);
-    d.insert(1, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(0, 1)], p);
-    mat.d[3] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[4] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[5] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(0, 1)], p);
-    d.insert(2, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(0, 1)], p);
-    mat.d[3] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[5] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(2, p - 1)], p);
-    d.insert(3, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(0, 1)], p);
-    mat.d[3] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[4] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[5] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[6] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(3, 1)], p);
-    d.insert(4, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(0, 1)], p);
-    mat.d[3] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[5] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[6] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(3, 1)], p);
-    d.insert(5, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[1] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[4] = Polynomial::new(vec![(0, 1)], p);
-    mat.d[8] = Polynomial::new(vec![(0, 1)], p);
-    d.insert(6, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[1] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[4] = Polynomial::new(vec![(0, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(2, p - 1)], p);
-    d.insert(7, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[1] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[2] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[3] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[4] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[5] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(0, 1)], p);
-    d.insert(8, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[2] = Polynomial::new(vec![(4, p - 1)], p);
-    mat.d[3] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[5] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(2, p - 1)], p);
-    d.insert(9, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[1] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[2] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[3] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[4] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[5] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[6] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(3, 1)], p);
-    d.insert(10, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[2] = Polynomial::new(vec![(4, p - 1)], p);
-    mat.d[3] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[5] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[6] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(3, 1)], p);
-    d.insert(11, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[1] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[3] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[5] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(0, 1)], p);
-    d.insert(12, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[1] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[3] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[4] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[5] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(2, p - 1)], p);
-    d.insert(13, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[1] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[2] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[3] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[5] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(0, 1)], p);
-    d.insert(14, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[2] = Polynomial::new(vec![(4, p - 1)], p);
-    mat.d[3] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[4] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[5] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[8] = Polynomial::new(vec![(2, p - 1)], p);
-    d.insert(15, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[1] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[2] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[4] = Polynomial::new(vec![(4, p - 1)], p);
-    mat.d[6] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(3, 1)], p);
-    d.insert(16, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[2] = Polynomial::new(vec![(4, p - 1)], p);
-    mat.d[4] = Polynomial::new(vec![(4, p - 1)], p);
-    mat.d[6] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[7] = Polynomial::new(vec![(3, 1)], p);
-    d.insert(17, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[1] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[3] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[5] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[6] = Polynomial::new(vec![(4, p - 1)], p);
-    d.insert(18, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[0] = Polynomial::new(vec![(2, p - 1)], p);
-    mat.d[1] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[3] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[4] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[5] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[6] = Polynomial::new(vec![(4, p - 1)], p);
-    d.insert(19, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[1] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[2] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[3] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[5] = Polynomial::new(vec![(1, p - 1)], p);
-    mat.d[6] = Polynomial::new(vec![(4, p - 1)], p);
-    d.insert(20, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[2] = Polynomial::new(vec![(4, p - 1)], p);
-    mat.d[3] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[4] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[5] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[6] = Polynomial::new(vec![(4, p - 1)], p);
-    d.insert(21, mat);
-    let mut mat = Matrix::zero(p);
-    mat.d[1] = Polynomial::new(vec![(3, 1)], p);
-    mat.d[2] = Polynomial::new(vec![(2, 1)], p);
-    mat.d[4] = Polynomial::new(vec![(4, p - 1)], p);
-    mat.d[6] = Polynomial::new(vec![(4, p - 1)], p);
-    d.insert(22, mat);
+    for factor in 1..=num_factors(n) {
+        d.insert(factor, act_by(&eye, factor, &table).unwrap());
+    }
     d
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::Polynomial;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg32;
+
+    #[test]
+    fn matrix_map_matches_b4_table() {
+        // Entries (index, power, negated) of the Burau matrices of B_4.
+        let table = vec![
+            (1u32, vec![(0usize, 0i32, false), (4, 0, false), (7, 1, true), (8, 2, true)]),
+            (2, vec![(0, 0, false), (3, 1, true), (4, 2, true), (5, 1, true), (8, 0, false)]),
+            (3, vec![(0, 0, false), (3, 1, true), (5, 3, false), (7, 1, true), (8, 2, true)]),
+            (
+                4,
+                vec![
+                    (0, 0, false),
+                    (3, 1, true),
+                    (4, 2, true),
+                    (5, 1, true),
+                    (6, 2, false),
+                    (7, 3, false),
+                ],
+            ),
+            (5, vec![(0, 0, false), (3, 1, true), (5, 3, false), (6, 2, false), (7, 3, false)]),
+            (6, vec![(0, 2, true), (1, 1, true), (4, 0, false), (8, 0, false)]),
+            (7, vec![(0, 2, true), (1, 1, true), (4, 0, false), (7, 1, true), (8, 2, true)]),
+            (
+                8,
+                vec![
+                    (1, 3, false),
+                    (2, 2, false),
+                    (3, 1, true),
+                    (4, 2, true),
+                    (5, 1, true),
+                    (8, 0, false),
+                ],
+            ),
+            (9, vec![(2, 4, true), (3, 1, true), (5, 3, false), (7, 1, true), (8, 2, true)]),
+            (
+                10,
+                vec![
+                    (1, 3, false),
+                    (2, 2, false),
+                    (3, 1, true),
+                    (4, 2, true),
+                    (5, 1, true),
+                    (6, 2, false),
+                    (7, 3, false),
+                ],
+            ),
+            (11, vec![(2, 4, true), (3, 1, true), (5, 3, false), (6, 2, false), (7, 3, false)]),
+            (12, vec![(0, 2, true), (1, 1, true), (3, 3, false), (5, 1, true), (8, 0, false)]),
+            (
+                13,
+                vec![
+                    (0, 2, true),
+                    (1, 1, true),
+                    (3, 3, false),
+                    (4, 2, false),
+                    (5, 3, false),
+                    (7, 1, true),
+                    (8, 2, true),
+                ],
+            ),
+            (14, vec![(1, 3, false), (2, 2, false), (3, 3, false), (5, 1, true), (8, 0, false)]),
+            (
+                15,
+                vec![
+                    (2, 4, true),
+                    (3, 3, false),
+                    (4, 2, false),
+                    (5, 3, false),
+                    (7, 1, true),
+                    (8, 2, true),
+                ],
+            ),
+            (16, vec![(1, 3, false), (2, 2, false), (4, 4, true), (6, 2, false), (7, 3, false)]),
+            (17, vec![(2, 4, true), (4, 4, true), (6, 2, false), (7, 3, false)]),
+            (18, vec![(0, 2, true), (1, 1, true), (3, 3, false), (5, 1, true), (6, 4, true)]),
+            (
+                19,
+                vec![
+                    (0, 2, true),
+                    (1, 1, true),
+                    (3, 3, false),
+                    (4, 2, false),
+                    (5, 3, false),
+                    (6, 4, true),
+                ],
+            ),
+            (20, vec![(1, 3, false), (2, 2, false), (3, 3, false), (5, 1, true), (6, 4, true)]),
+            (21, vec![(2, 4, true), (3, 3, false), (4, 2, false), (5, 3, false), (6, 4, true)]),
+            (22, vec![(1, 3, false), (2, 2, false), (4, 4, true), (6, 4, true)]),
+        ];
+        let p = 7;
+        let map = generate_matrix_map(4, p);
+        assert_eq!(map.len(), table.len());
+        for (factor, entries) in table {
+            let mut mat = Matrix::zero(3, p);
+            for (i, power, neg) in entries {
+                mat.d[i] = Polynomial::one(p).mult(power, neg);
+            }
+            assert_eq!(map[&factor], mat, "factor {}", factor);
+        }
+    }
+
+    #[test]
+    fn act_by_composes_generators() {
+        let p = 5;
+        let table = ActionTable::new(4);
+        let mut mat = act_by(&Matrix::identity(3, p), 13, &table).unwrap();
+        mat = act_by(&mat, 20, &table).unwrap();
+        let mut expected = Matrix::identity(3, p);
+        for factor in [13, 20] {
+            for i in simple_element(4, factor).positive_word() {
+                expected = act_by_generator(&expected, i as i32);
+            }
+        }
+        assert_eq!(mat, expected);
+    }
+
+    #[test]
+    fn act_by_matches_matrix_product() {
+        for n in [4, 5] {
+            let p = 7;
+            let table = ActionTable::new(n);
+            let map = generate_matrix_map(n, p);
+            // A matrix with non-trivial entries everywhere.
+            let mut mat = Matrix::identity(n - 1, p);
+            for factor in [2, 9, 7] {
+                mat = act_by(&mat, factor, &table).unwrap();
+            }
+            for factor in 1..=num_factors(n) {
+                let expected = &mat * &map[&factor];
+                assert_eq!(act_by(&mat, factor, &table).unwrap(), expected, "factor {}", factor);
+            }
+        }
+    }
+
+    #[test]
+    fn act_by_rejects_unknown_factor() {
+        let table = ActionTable::new(4);
+        let eye = Matrix::identity(3, 5);
+        assert_eq!(act_by(&eye, 23, &table), Err(UnknownFactor { factor: 23, n: 4 }));
+        assert!(act_by(&eye, 0, &table).is_err());
+    }
+
+    #[test]
+    fn descendants_match_b4_table() {
+        let table = vec![
+            (1, vec![1, 4, 18]),
+            (2, vec![2, 3, 12, 13, 16]),
+            (3, vec![1, 4, 18]),
+            (4, vec![2, 3, 12, 13, 16]),
+            (5, vec![1, 2, 3, 4, 5, 12, 13, 16, 18, 19, 22]),
+            (6, vec![6, 8, 9]),
+            (7, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]),
+            (8, vec![2, 3, 12, 13, 16]),
+            (9, vec![1, 4, 18]),
+            (10, vec![2, 3, 12, 13, 16]),
+            (11, vec![1, 2, 3, 4, 5, 12, 13, 16, 18, 19, 22]),
+            (12, vec![6, 8, 9]),
+            (13, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]),
+            (14, vec![2, 3, 6, 8, 9, 12, 13, 14, 15, 16, 17]),
+            (15, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]),
+            (16, vec![2, 3, 12, 13, 16]),
+            (17, vec![1, 2, 3, 4, 5, 12, 13, 16, 18, 19, 22]),
+            (18, vec![6, 8, 9]),
+            (19, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]),
+            (20, vec![2, 3, 6, 8, 9, 12, 13, 14, 15, 16, 17]),
+            (21, vec![1, 4, 6, 7, 8, 9, 10, 11, 18, 20, 21]),
+            (22, vec![2, 3, 6, 8, 9, 12, 13, 14, 15, 16, 17]),
+        ];
+        let descendants = generate_descendants(4);
+        assert_eq!(descendants.len(), table.len());
+        for (factor, expected) in table {
+            assert_eq!(descendants[&factor], expected, "factor {}", factor);
+        }
+    }
+
+    #[test]
+    fn simple_elements_b4_words() {
+        assert_eq!(simple_elements(4).len(), 22);
+        assert_eq!(simple_element(4, 1).positive_word(), vec![3]);
+        assert_eq!(simple_element(4, 13).positive_word(), vec![2, 3, 1]);
+        assert_eq!(simple_element(4, 22).positive_word(), vec![2, 3, 1, 2, 1]);
+    }
+
+    #[test]
+    fn sigma_1_descendants_b5() {
+        let simples = simple_elements(5);
+        let descendants = generate_descendants(5);
+        assert_eq!(descendants.len(), 118);
+        let sigma1 = simples.iter().position(|x| x.positive_word() == vec![1]).unwrap() as u32 + 1;
+        // σ_1 may only be followed by the four factors with starting set {σ_1}.
+        assert_eq!(descendants[&sigma1].len(), 4);
+        for k in &descendants[&sigma1] {
+            assert_eq!(simples[*k as usize - 1].left_descents(), 0b10);
+        }
+    }
+
+    fn normal_form_of(n: usize, delta_power: i32, factors: &[u32]) -> (i32, Vec<u32>) {
+        let simples: Vec<Permutation> = factors.iter().map(|x| simple_element(n, *x)).collect();
+        left_normal_form(n, delta_power, &simples)
+    }
+
+    #[test]
+    fn left_normal_form_is_stable() {
+        let factors = vec![17, 19, 10, 13, 8, 13, 11, 13, 10, 16, 13, 1, 4];
+        assert_eq!(normal_form_of(4, 0, &factors), (0, factors.clone()));
+        assert_eq!(normal_form_of(4, -3, &factors), (-3, factors));
+    }
+
+    #[test]
+    fn left_normal_form_b4() {
+        // σ1·σ1 is left-weighted, while σ1·σ2σ1 is the simple σ1σ2σ1.
+        assert_eq!(normal_form_of(4, 0, &[6, 6]), (0, vec![6, 6]));
+        assert_eq!(normal_form_of(4, 0, &[6, 12]), (0, vec![14]));
+        // σ1σ2σ3·σ3σ2σ1 is left-weighted, while σ2σ3σ1σ2σ1·σ3 = Δ.
+        assert_eq!(normal_form_of(4, 0, &[9, 18]), (0, vec![9, 18]));
+        assert_eq!(normal_form_of(4, 1, &[22, 1]), (2, vec![]));
+        // σ3·Δ = Δ·σ1.
+        let delta = Permutation::half_twist(4);
+        let simples = [simple_element(4, 1), delta];
+        assert_eq!(left_normal_form(4, 0, &simples), (1, vec![6]));
+    }
+
+    #[test]
+    fn quotient_times_divisor_is_dividend() {
+        let a = vec![17, 19, 10, 13, 8];
+        let b = vec![13, 10, 13, 11, 2];
+        let (k, mut factors) = quotient(4, &a, &b);
+        factors.extend(&b);
+        assert_eq!(normal_form_of(4, k, &factors), (0, a.clone()));
+        assert_eq!(quotient(4, &a, &a), (0, vec![]));
+        for x in 1..=num_factors(4) {
+            for y in 1..=num_factors(4) {
+                let (k, factors) = quotient(4, &[x, y], &[y]);
+                let b: Vec<u32> = factors.iter().chain(&[y]).copied().collect();
+                assert_eq!(normal_form_of(4, k, &b), normal_form_of(4, 0, &[x, y]));
+            }
+        }
+        // σ1⁻¹ = Δ⁻¹·σ1σ2σ3σ1σ2.
+        assert_eq!(quotient(4, &[], &[6]), (-1, vec![17]));
+    }
+
+    #[test]
+    fn burau_image_of_delta_powers() {
+        let table = ActionTable::new(4);
+        let eye = Matrix::identity(3, 5);
+        let complement = |x: u32| simple_element(4, x).complement().rank() as u32;
+        let delta = burau_image(1, &[], &table, 5).unwrap();
+        let sigma1 = act_by(&eye, 6, &table).unwrap();
+        assert_eq!(delta, act_by(&sigma1, complement(6), &table).unwrap());
+        // Δ² is central with image v^8·I.
+        let full_twist = Matrix {
+            d: eye.d.iter().map(|x| x.mult(8, false)).collect(),
+            ..eye.clone()
+        };
+        assert_eq!(burau_image(2, &[], &table, 5), Ok(full_twist.clone()));
+        let inverse = Matrix {
+            d: eye.d.iter().map(|x| x.mult(-8, false)).collect(),
+            ..eye.clone()
+        };
+        assert_eq!(burau_image(-2, &[], &table, 5), Ok(inverse));
+        let factors = [6, complement(6), 6, complement(6)];
+        assert_eq!(burau_image(-1, &factors, &table, 5), Ok(delta));
+        assert!(burau_image(0, &[23], &table, 5).is_err());
+    }
+
+    #[test]
+    fn act_by_dense_agrees_with_act_by() {
+        let table = ActionTable::new(4);
+        let mut rng = Pcg32::seed_from_u64(1);
+        for p in [2, 3, 5, 4294967291] {
+            let mut mat = Matrix::identity(3, p);
+            let mut dense = DenseMatrix::identity(3, p);
+            let mut out = DenseMatrix::zero(3, p);
+            for _ in 0..200 {
+                let factor = rng.gen_range(1..=22);
+                mat = act_by(&mat, factor, &table).unwrap();
+                act_by_dense(&dense, factor, &table, &mut out).unwrap();
+                std::mem::swap(&mut dense, &mut out);
+                assert_eq!(Matrix::from(&dense), mat);
+                assert_eq!(dense.projlen(), mat.projlen());
+            }
+        }
+        let eye = DenseMatrix::identity(3, 5);
+        assert!(act_by_dense(&eye, 23, &table, &mut eye.clone()).is_err());
+    }
+
+    #[test]
+    fn act_by_inverse_undoes_act_by() {
+        for n in [3, 4, 5] {
+            let table = ActionTable::new(n);
+            let eye = Matrix::identity(n - 1, 7);
+            for factor in 1..=num_factors(n) {
+                let mat = act_by(&eye, factor, &table).unwrap();
+                assert_eq!(act_by_inverse(&mat, factor, &table).unwrap(), eye);
+                let mat = act_by_inverse(&eye, factor, &table).unwrap();
+                assert_eq!(act_by(&mat, factor, &table).unwrap(), eye);
+            }
+            assert!(act_by_inverse(&eye, num_factors(n) + 1, &table).is_err());
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Laurent polynomials with integer coefficients and matrices of them, for
+//! computing the reduced Burau representation over Z[v, v⁻¹] exactly and
+//! comparing it with its reductions mod p.
+
+use num::bigint::BigInt;
+use num::{Integer, One, Signed, ToPrimitive, Zero};
+use std::fmt;
+use std::ops::{Add, Mul};
+
+use crate::algebra::{LaurentMatrix, Matrix, Polynomial};
+
+/// A Laurent polynomial in v over the integers, stored like `Polynomial` as
+/// its non-zero terms in increasing order of their powers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntPolynomial {
+    pub powers: Vec<i32>,
+    pub coefs: Vec<BigInt>,
+}
+
+impl IntPolynomial {
+    pub fn new(elements: Vec<(i32, BigInt)>) -> IntPolynomial {
+        let (powers, coefs) = elements.into_iter().unzip();
+        IntPolynomial { powers, coefs }
+    }
+
+    pub fn zero() -> IntPolynomial {
+        Self::new(vec![])
+    }
+
+    pub fn one() -> IntPolynomial {
+        Self::new(vec![(0, BigInt::one())])
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.powers.is_empty()
+    }
+
+    pub fn max_power(&self) -> i32 {
+        *self.powers.last().unwrap()
+    }
+
+    pub fn min_power(&self) -> i32 {
+        *self.powers.first().unwrap()
+    }
+
+    /// Multiplies by ±v^power.
+    pub fn mult(&self, power: i32, neg: bool) -> IntPolynomial {
+        IntPolynomial {
+            powers: self.powers.iter().map(|x| x + power).collect(),
+            coefs: if neg {
+                self.coefs.iter().map(|x| -x).collect()
+            } else {
+                self.coefs.clone()
+            },
+        }
+    }
+
+    /// The polynomial with every coefficient reduced mod p.
+    pub fn reduce(&self, p: u32) -> Polynomial {
+        let modulus = BigInt::from(p);
+        let terms = self
+            .powers
+            .iter()
+            .zip(&self.coefs)
+            .map(|(power, coef)| (*power, coef.mod_floor(&modulus).to_u32().unwrap()))
+            .filter(|x| x.1 != 0)
+            .collect();
+        Polynomial::new(terms, p)
+    }
+}
+
+impl Add for &IntPolynomial {
+    type Output = IntPolynomial;
+
+    fn add(self, rhs: &IntPolynomial) -> IntPolynomial {
+        let mut res = IntPolynomial::zero();
+        let (mut i, mut j) = (0, 0);
+        while i < self.powers.len() || j < rhs.powers.len() {
+            let (power, coef) = if j == rhs.powers.len() || i < self.powers.len() && self.powers[i] < rhs.powers[j] {
+                i += 1;
+                (self.powers[i - 1], self.coefs[i - 1].clone())
+            } else if i == self.powers.len() || rhs.powers[j] < self.powers[i] {
+                j += 1;
+                (rhs.powers[j - 1], rhs.coefs[j - 1].clone())
+            } else {
+                i += 1;
+                j += 1;
+                (self.powers[i - 1], &self.coefs[i - 1] + &rhs.coefs[j - 1])
+            };
+            if !coef.is_zero() {
+                res.powers.push(power);
+                res.coefs.push(coef);
+            }
+        }
+        res
+    }
+}
+impl Add for IntPolynomial {
+    type Output = IntPolynomial;
+
+    fn add(self, rhs: IntPolynomial) -> IntPolynomial {
+        &self + &rhs
+    }
+}
+
+impl Mul for &IntPolynomial {
+    type Output = IntPolynomial;
+
+    fn mul(self, rhs: &IntPolynomial) -> IntPolynomial {
+        if self.is_zero() || rhs.is_zero() {
+            return IntPolynomial::zero();
+        }
+        let shift = self.min_power() + rhs.min_power();
+        let mut dense = vec![BigInt::zero(); (self.max_power() + rhs.max_power() - shift) as usize + 1];
+        for (x_power, x) in self.powers.iter().zip(&self.coefs) {
+            for (y_power, y) in rhs.powers.iter().zip(&rhs.coefs) {
+                dense[(x_power + y_power - shift) as usize] += x * y;
+            }
+        }
+        let terms = dense
+            .into_iter()
+            .enumerate()
+            .filter(|x| !x.1.is_zero())
+            .map(|(i, coef)| (shift + i as i32, coef))
+            .collect();
+        IntPolynomial::new(terms)
+    }
+}
+impl Mul for IntPolynomial {
+    type Output = IntPolynomial;
+
+    fn mul(self, rhs: IntPolynomial) -> IntPolynomial {
+        &self * &rhs
+    }
+}
+
+/// Prints the polynomial in increasing powers of v, as in `1 - 2v + v^-3`.
+impl fmt::Display for IntPolynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        for (i, (power, coef)) in self.powers.iter().zip(&self.coefs).enumerate() {
+            match (i, coef.is_negative()) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            let c = coef.abs();
+            match *power {
+                0 => write!(f, "{}", c)?,
+                1 if c.is_one() => write!(f, "v")?,
+                1 => write!(f, "{}v", c)?,
+                k if c.is_one() => write!(f, "v^{}", k)?,
+                k => write!(f, "{}v^{}", c, k)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A square matrix of integral Laurent polynomials, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntMatrix {
+    pub d: Vec<IntPolynomial>,
+    pub dim: usize,
+}
+
+impl IntMatrix {
+    pub fn zero(dim: usize) -> IntMatrix {
+        IntMatrix {
+            d: vec![IntPolynomial::zero(); dim * dim],
+            dim,
+        }
+    }
+
+    pub fn identity(dim: usize) -> IntMatrix {
+        let mut res = Self::zero(dim);
+        for i in 0..dim {
+            res.d[i * dim + i] = IntPolynomial::one();
+        }
+        res
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> &IntPolynomial {
+        &self.d[row * self.dim + col]
+    }
+
+    /// The matrix with every entry reduced mod p.
+    pub fn reduce(&self, p: u32) -> Matrix {
+        Matrix {
+            d: self.d.iter().map(|x| x.reduce(p)).collect(),
+            dim: self.dim,
+            p,
+        }
+    }
+
+    /// Whether the matrix is ±v^k·I for some k.
+    pub fn is_scalar(&self) -> bool {
+        let diagonal = self.get(0, 0);
+        if diagonal.powers.len() != 1 || !diagonal.coefs[0].abs().is_one() {
+            return false;
+        }
+        for row in 0..self.dim {
+            for col in 0..self.dim {
+                let entry = self.get(row, col);
+                if row == col && entry != diagonal || row != col && !entry.is_zero() {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl LaurentMatrix for IntMatrix {
+    fn dim(&self) -> usize {
+        self.dim
+    }
+
+    fn zero_like(&self) -> IntMatrix {
+        IntMatrix::zero(self.dim)
+    }
+
+    fn add_column(&mut self, dst: usize, other: &IntMatrix, src: usize, power: i32, neg: bool) {
+        let dim = self.dim;
+        for row in 0..dim {
+            let x = other.get(row, src);
+            if x.is_zero() {
+                continue;
+            }
+            let target = &mut self.d[row * dim + dst];
+            *target = &*target + &x.mult(power, neg);
+        }
+    }
+}
+
+/// Prints the matrix with one bracketed row per line.
+impl fmt::Display for IntMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..self.dim {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            let entries: Vec<String> = (0..self.dim).map(|col| self.get(row, col).to_string()).collect();
+            write!(f, "[{}]", entries.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Mul for &IntMatrix {
+    type Output = IntMatrix;
+
+    fn mul(self, rhs: &IntMatrix) -> IntMatrix {
+        assert_eq!(self.dim, rhs.dim, "cannot multiply matrices of different dimensions");
+        let dim = self.dim;
+        let mut res = IntMatrix::zero(dim);
+        for i in 0..dim {
+            for k in 0..dim {
+                let x = self.get(i, k);
+                if x.is_zero() {
+                    continue;
+                }
+                for j in 0..dim {
+                    let y = rhs.get(k, j);
+                    if y.is_zero() {
+                        continue;
+                    }
+                    let entry = &mut res.d[i * dim + j];
+                    *entry = &*entry + &(x * y);
+                }
+            }
+        }
+        res
+    }
+}
+impl Mul for IntMatrix {
+    type Output = IntMatrix;
+
+    fn mul(self, rhs: IntMatrix) -> IntMatrix {
+        &self * &rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(terms: &[(i32, i64)]) -> IntPolynomial {
+        IntPolynomial::new(terms.iter().map(|(power, coef)| (*power, BigInt::from(*coef))).collect())
+    }
+
+    #[test]
+    fn add_and_multiply() {
+        let a = poly(&[(-1, 1), (0, -2), (3, 5)]);
+        let b = poly(&[(0, 2), (3, -5)]);
+        assert_eq!(&a + &b, poly(&[(-1, 1)]));
+        assert_eq!(&a * &b, poly(&[(-1, 2), (0, -4), (2, -5), (3, 20), (6, -25)]));
+        assert_eq!(&a * &IntPolynomial::one(), a);
+        assert!((&a * &IntPolynomial::zero()).is_zero());
+        assert_eq!(a.mult(2, true), poly(&[(1, -1), (2, 2), (5, -5)]));
+    }
+
+    #[test]
+    fn coefficients_do_not_overflow() {
+        let mut x = poly(&[(0, 1), (1, 1)]);
+        for _ in 0..7 {
+            x = &x * &x;
+        }
+        // (1 + v)^128 has the central coefficient binomial(128, 64) > 2^64.
+        let central = &x.coefs[64];
+        assert!(central.to_u64().is_none());
+        assert_eq!(x.reduce(3), {
+            let mut y = Polynomial::new(vec![(0, 1), (1, 1)], 3);
+            for _ in 0..7 {
+                y = &y * &y;
+            }
+            y
+        });
+    }
+
+    #[test]
+    fn reduce_and_display() {
+        let a = poly(&[(-3, 1), (0, -2), (1, 7), (2, -1)]);
+        assert_eq!(a.to_string(), "v^-3 - 2 + 7v - v^2");
+        assert_eq!(poly(&[(1, -1)]).to_string(), "-v");
+        assert_eq!(a.reduce(7), Polynomial::new(vec![(-3, 1), (0, 5), (2, 6)], 7));
+        let mut mat = IntMatrix::identity(2);
+        mat.d[1] = a;
+        assert_eq!(mat.to_string(), "[1, v^-3 - 2 + 7v - v^2]\n[0, 1]");
+        assert_eq!(mat.reduce(7).get(0, 1), &Polynomial::new(vec![(-3, 1), (0, 5), (2, 6)], 7));
+        assert!(!mat.is_scalar());
+        assert!(IntMatrix::identity(3).is_scalar());
+    }
+}
@@ -0,0 +1,46 @@
+//! Searching for kernel elements of the Burau representation of the braid
+//! group B_n modulo a prime p.
+//!
+//! - [`algebra`] contains polynomials over F_p and matrices of them.
+//! - [`packed`] stores the coefficients of the dense matrices used by the
+//!   searches, bit-sliced for p = 2 and p = 3.
+//! - [`integral`] contains the same over the integers, for the Burau
+//!   representation in characteristic zero.
+//! - [`permutation`] and [`garside`] describe the Garside structure of B_n:
+//!   its simple elements, which of them may follow which in left normal form,
+//!   and their right action on reduced Burau matrices.
+//! - [`braid`] provides braids with the group operations of B_n, computed on
+//!   left normal forms.
+//! - [`search`] contains the search [`State`](search::State) and the search
+//!   strategies.
+//! - [`queue`] provides the bounded queues bucketed by projlen that hold the
+//!   frontiers of the searches.
+//! - [`tree`] stores the factors of the states of a search as paths in a
+//!   shared tree.
+//! - [`checkpoint`] saves and restores the frontier of long-running searches.
+//! - [`collision`] searches for pairs of braids with the same projective
+//!   Burau image, whose quotient is a kernel element.
+//! - [`findings`] collects the distinct kernel elements found by a search.
+//! - [`report`] prints the progress and results of a search, as text or as
+//!   JSON Lines.
+//! - [`transposition`] drops states whose projective Burau image has already
+//!   been reached.
+//! - [`verify`] checks claimed kernel elements independently of the search.
+//! - [`word`] writes braids as words in the Artin generators.
+
+pub mod algebra;
+pub mod braid;
+pub mod checkpoint;
+pub mod collision;
+pub mod findings;
+pub mod garside;
+pub mod integral;
+pub mod packed;
+pub mod permutation;
+pub mod queue;
+pub mod report;
+pub mod search;
+pub mod transposition;
+pub mod tree;
+pub mod verify;
+pub mod word;
@@ -0,0 +1,303 @@
+//! Storage of the coefficients of a [`DenseMatrix`](crate::algebra::DenseMatrix),
+//! packed according to the prime. Any prime uses one `u32` per coefficient,
+//! while p = 2 and p = 3 use bit-sliced words, in which the window of powers
+//! of an entry occupies a few `u64` and additions are word operations.
+
+/// The coefficients of every entry of a matrix over one window of powers,
+/// entry after entry. Each entry takes `stride` values or words, where the
+/// coefficient of the `k`th power of the window is at value `k`, or at bit
+/// `k % 64` of word `k / 64`. Bits beyond the window are always zero.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Coefficients {
+    /// One `u32` per coefficient, for any prime.
+    Wide(Vec<u32>),
+    /// One bit per coefficient, for p = 2, where addition is XOR.
+    Bits(Vec<u64>),
+    /// For p = 3, one bit plane marking the coefficients equal to 1 and one
+    /// marking those equal to 2, so that negation swaps the planes.
+    Trits(Vec<u64>, Vec<u64>),
+}
+
+/// Sum of two vectors of elements of F_3, each given by the planes of its
+/// ones and twos.
+fn add_trits(a1: u64, a2: u64, b1: u64, b2: u64) -> (u64, u64) {
+    let a0 = !(a1 | a2);
+    let b0 = !(b1 | b2);
+    ((a1 & b0) | (a0 & b1) | (a2 & b2), (a2 & b0) | (a0 & b2) | (a1 & b1))
+}
+
+/// Word `d` of the bits of `src` shifted up by `64 * q + r` bits.
+fn shifted_up(src: &[u64], q: usize, r: usize, d: usize) -> u64 {
+    let word = |i: usize| if i >= q && i - q < src.len() { src[i - q] } else { 0 };
+    if r == 0 {
+        word(d)
+    } else {
+        (word(d) << r) | (if d > 0 { word(d - 1) >> (64 - r) } else { 0 })
+    }
+}
+
+/// Moves the entries of `words`, `old_stride` words each, to `new_stride`
+/// words each, shifting their bits down by `low`. Every bit below `low` must
+/// be zero, and `new_stride` must not exceed `old_stride`.
+fn shift_down(words: &mut Vec<u64>, entries: usize, old_stride: usize, new_stride: usize, low: usize) {
+    let (q, r) = (low / 64, low % 64);
+    // Every word moves towards the start of the buffer and is read before
+    // any word that could overwrite it.
+    for i in 0..entries {
+        for d in 0..new_stride {
+            let at = |j: usize| if j < old_stride { words[i * old_stride + j] } else { 0 };
+            let word = if r == 0 {
+                at(d + q)
+            } else {
+                (at(d + q) >> r) | (at(d + q + 1) << (64 - r))
+            };
+            words[i * new_stride + d] = word;
+        }
+    }
+    words.truncate(entries * new_stride);
+}
+
+/// Calls `update` with the lowest and highest set bit of every non-zero word,
+/// counted from the start of its entry of `stride` words, where the words of
+/// `twos`, if given, are combined with those of `words`.
+fn scan_words(words: &[u64], twos: Option<&[u64]>, stride: usize, update: &mut impl FnMut(usize, usize)) {
+    for (i, word) in words.iter().enumerate() {
+        let word = word | twos.map_or(0, |x| x[i]);
+        if word != 0 {
+            let d = i % stride;
+            update(64 * d + word.trailing_zeros() as usize, 64 * d + 63 - word.leading_zeros() as usize);
+        }
+    }
+}
+
+impl Coefficients {
+    /// Empty storage of the packing used for the prime `p`.
+    pub fn new(p: u32) -> Coefficients {
+        match p {
+            2 => Coefficients::Bits(Vec::new()),
+            3 => Coefficients::Trits(Vec::new(), Vec::new()),
+            _ => Coefficients::Wide(Vec::new()),
+        }
+    }
+
+    /// Values or words taken by an entry with a window of `width` powers.
+    pub fn stride(&self, width: usize) -> usize {
+        match self {
+            Coefficients::Wide(_) => width,
+            _ => width.div_ceil(64),
+        }
+    }
+
+    /// Sets all of `entries` entries of `stride` values or words to zero,
+    /// reusing the allocation.
+    pub fn reset(&mut self, entries: usize, stride: usize) {
+        match self {
+            Coefficients::Wide(values) => {
+                values.clear();
+                values.resize(entries * stride, 0);
+            }
+            Coefficients::Bits(words) => {
+                words.clear();
+                words.resize(entries * stride, 0);
+            }
+            Coefficients::Trits(ones, twos) => {
+                ones.clear();
+                ones.resize(entries * stride, 0);
+                twos.clear();
+                twos.resize(entries * stride, 0);
+            }
+        }
+    }
+
+    /// The coefficient of the `k`th power of entry `i`.
+    pub fn get(&self, stride: usize, i: usize, k: usize) -> u32 {
+        let bit = |words: &[u64]| (words[i * stride + k / 64] >> (k % 64)) as u32 & 1;
+        match self {
+            Coefficients::Wide(values) => values[i * stride + k],
+            Coefficients::Bits(words) => bit(words),
+            Coefficients::Trits(ones, twos) => bit(ones) + 2 * bit(twos),
+        }
+    }
+
+    /// Sets the coefficient of the `k`th power of entry `i`, which must be
+    /// zero, to `value`.
+    pub fn set(&mut self, stride: usize, i: usize, k: usize, value: u32) {
+        let (word, bit) = (i * stride + k / 64, 1 << (k % 64));
+        match self {
+            Coefficients::Wide(values) => values[i * stride + k] = value,
+            Coefficients::Bits(words) if value == 1 => words[word] |= bit,
+            Coefficients::Trits(ones, _) if value == 1 => ones[word] |= bit,
+            Coefficients::Trits(_, twos) if value == 2 => twos[word] |= bit,
+            _ => {}
+        }
+    }
+
+    /// Adds ±v^offset times entry `src_i` of `src` to entry `i`, where the
+    /// shifted entry must fit in the window. Both must use the same packing.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_shifted(
+        &mut self,
+        stride: usize,
+        i: usize,
+        src: &Coefficients,
+        src_stride: usize,
+        src_i: usize,
+        offset: usize,
+        neg: bool,
+        p: u32,
+    ) {
+        let (q, r) = (offset / 64, offset % 64);
+        let from = src_i * src_stride..(src_i + 1) * src_stride;
+        // Only the words that the shifted entry can reach.
+        let words = q..stride.min(q + src_stride + 1);
+        match (self, src) {
+            (Coefficients::Wide(values), Coefficients::Wide(src)) => {
+                let p = p as u64;
+                let start = i * stride + offset;
+                for (y, x) in values[start..start + src_stride].iter_mut().zip(&src[from]) {
+                    let sum = *y as u64 + if neg { p - *x as u64 } else { *x as u64 };
+                    *y = if sum >= p { sum - p } else { sum } as u32;
+                }
+            }
+            (Coefficients::Bits(dst), Coefficients::Bits(src)) => {
+                let src = &src[from];
+                for d in words {
+                    dst[i * stride + d] ^= shifted_up(src, q, r, d);
+                }
+            }
+            (Coefficients::Trits(ones, twos), Coefficients::Trits(src_ones, src_twos)) => {
+                let (src_ones, src_twos) = if neg { (src_twos, src_ones) } else { (src_ones, src_twos) };
+                let (src_ones, src_twos) = (&src_ones[from.clone()], &src_twos[from]);
+                for d in words {
+                    let j = i * stride + d;
+                    let b1 = shifted_up(src_ones, q, r, d);
+                    let b2 = shifted_up(src_twos, q, r, d);
+                    (ones[j], twos[j]) = add_trits(ones[j], twos[j], b1, b2);
+                }
+            }
+            _ => panic!("cannot add coefficients of different packings"),
+        }
+    }
+
+    /// The lowest and highest power of the window with a non-zero
+    /// coefficient in some of the `entries` entries, if there is one.
+    pub fn support(&self, entries: usize, stride: usize) -> Option<(usize, usize)> {
+        let mut res: Option<(usize, usize)> = None;
+        let mut update = |low: usize, high: usize| {
+            res = Some(res.map_or((low, high), |(x, y)| (x.min(low), y.max(high))));
+        };
+        match self {
+            Coefficients::Wide(values) => {
+                for entry in values.chunks(stride.max(1)).take(entries) {
+                    if let Some(low) = entry.iter().position(|x| *x != 0) {
+                        update(low, entry.iter().rposition(|x| *x != 0).unwrap());
+                    }
+                }
+            }
+            Coefficients::Bits(words) => scan_words(words, None, stride, &mut update),
+            Coefficients::Trits(ones, twos) => scan_words(ones, Some(twos.as_slice()), stride, &mut update),
+        }
+        res
+    }
+
+    /// Narrows the window of every entry from `old_width` powers to the
+    /// `new_width` powers starting at the `low`th, all others being zero.
+    pub fn narrow(&mut self, entries: usize, old_width: usize, new_width: usize, low: usize) {
+        let (old_stride, new_stride) = (self.stride(old_width), self.stride(new_width));
+        match self {
+            Coefficients::Wide(values) => {
+                // Every entry moves towards the start of the buffer, so
+                // copying them in order never overwrites one that has yet to
+                // be moved.
+                for i in 0..entries {
+                    let start = i * old_width + low;
+                    values.copy_within(start..start + new_width, i * new_width);
+                }
+                values.truncate(entries * new_width);
+            }
+            Coefficients::Bits(words) => shift_down(words, entries, old_stride, new_stride, low),
+            Coefficients::Trits(ones, twos) => {
+                shift_down(ones, entries, old_stride, new_stride, low);
+                shift_down(twos, entries, old_stride, new_stride, low);
+            }
+        }
+    }
+
+    /// Multiplies every coefficient by the non-zero constant `c`.
+    pub fn scale(&mut self, c: u32, p: u32) {
+        match self {
+            Coefficients::Wide(values) => {
+                for x in values.iter_mut() {
+                    *x = (*x as u64 * c as u64 % p as u64) as u32;
+                }
+            }
+            Coefficients::Bits(_) => {}
+            Coefficients::Trits(ones, twos) => {
+                if c == 2 {
+                    std::mem::swap(ones, twos);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg32;
+
+    #[test]
+    fn trits_add_like_integers_mod_three() {
+        let planes = |x: u32| ((x == 1) as u64, (x == 2) as u64);
+        for x in 0..3 {
+            for y in 0..3 {
+                let ((a1, a2), (b1, b2)) = (planes(x), planes(y));
+                assert_eq!(add_trits(a1, a2, b1, b2), planes((x + y) % 3), "{} + {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn packings_agree_with_wide_coefficients() {
+        let mut rng = Pcg32::seed_from_u64(0);
+        for p in [2, 3] {
+            for (src_width, offset, width) in [(3, 0, 5), (40, 30, 100), (64, 64, 130), (100, 27, 130), (70, 1, 71)] {
+                let mut packed = Coefficients::new(p);
+                let mut wide = Coefficients::Wide(Vec::new());
+                let (stride, wide_stride) = (packed.stride(width), wide.stride(width));
+                packed.reset(2, stride);
+                wide.reset(2, wide_stride);
+                let mut src = Coefficients::new(p);
+                let mut wide_src = Coefficients::Wide(Vec::new());
+                src.reset(1, src.stride(src_width));
+                wide_src.reset(1, src_width);
+                for k in 0..width {
+                    let x = rng.gen_range(0..p);
+                    packed.set(stride, 1, k, x);
+                    wide.set(wide_stride, 1, k, x);
+                }
+                for k in 0..src_width {
+                    let x = rng.gen_range(0..p);
+                    src.set(src.stride(src_width), 0, k, x);
+                    wide_src.set(src_width, 0, k, x);
+                }
+                let neg = rng.gen();
+                packed.add_shifted(stride, 1, &src, src.stride(src_width), 0, offset, neg, p);
+                wide.add_shifted(wide_stride, 1, &wide_src, src_width, 0, offset, neg, p);
+                for k in 0..width {
+                    assert_eq!(packed.get(stride, 1, k), wide.get(wide_stride, 1, k));
+                    assert_eq!(packed.get(stride, 0, k), 0);
+                }
+                let (low, high) = wide.support(2, wide_stride).unwrap();
+                assert_eq!(packed.support(2, stride), Some((low, high)));
+                packed.narrow(2, width, high - low + 1, low);
+                wide.narrow(2, width, high - low + 1, low);
+                let stride = packed.stride(high - low + 1);
+                for k in 0..high - low + 1 {
+                    assert_eq!(packed.get(stride, 1, k), wide.get(high - low + 1, 1, k));
+                }
+            }
+        }
+    }
+}
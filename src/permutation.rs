@@ -0,0 +1,237 @@
+use std::ops::Mul;
+
+/// A permutation of `0..n`, stored as the sequence of its images. Positive
+/// permutation braids (the simple elements of the Garside structure on B_n)
+/// are in bijection with these.
+///
+/// The conventions match the Burau action in `garside`: multiplying on the
+/// right by the Artin generator σ_i swaps the entries in positions `i - 1`
+/// and `i`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Permutation {
+    pub images: Vec<usize>,
+}
+
+impl Permutation {
+    pub fn identity(n: usize) -> Permutation {
+        Permutation {
+            images: (0..n).collect(),
+        }
+    }
+
+    /// The permutation reversing `0..n`, underlying the half twist Δ.
+    pub fn half_twist(n: usize) -> Permutation {
+        Permutation {
+            images: (0..n).rev().collect(),
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.images.iter().enumerate().all(|(i, x)| i == *x)
+    }
+
+    /// All permutations of `0..n` in lexicographic order of their images.
+    pub fn all(n: usize) -> Vec<Permutation> {
+        let mut res = Vec::new();
+        let mut current = Self::identity(n);
+        loop {
+            res.push(current.clone());
+            if !current.next_lexicographic() {
+                return res;
+            }
+        }
+    }
+
+    /// The permutation at index `rank` in the lexicographic order used by
+    /// `all`.
+    pub fn from_rank(n: usize, mut rank: usize) -> Permutation {
+        let mut remaining: Vec<usize> = (0..n).collect();
+        let mut images = Vec::with_capacity(n);
+        for i in (0..n).rev() {
+            let f = factorial(i);
+            images.push(remaining.remove(rank / f));
+            rank %= f;
+        }
+        Permutation { images }
+    }
+
+    /// The index of the permutation in the lexicographic order used by `all`.
+    pub fn rank(&self) -> usize {
+        let n = self.images.len();
+        let mut rank = 0;
+        for (i, x) in self.images.iter().enumerate() {
+            let smaller_later = self.images[i + 1..].iter().filter(|y| *y < x).count();
+            rank += smaller_later * factorial(n - 1 - i);
+        }
+        rank
+    }
+
+    fn next_lexicographic(&mut self) -> bool {
+        let a = &mut self.images;
+        if a.len() < 2 {
+            return false;
+        }
+        let mut i = a.len() - 1;
+        while i > 0 && a[i - 1] >= a[i] {
+            i -= 1;
+        }
+        if i == 0 {
+            return false;
+        }
+        let mut j = a.len() - 1;
+        while a[j] <= a[i - 1] {
+            j -= 1;
+        }
+        a.swap(i - 1, j);
+        a[i..].reverse();
+        true
+    }
+
+    /// Position of every value, i.e. the inverse permutation.
+    pub fn inverse(&self) -> Permutation {
+        let mut images = vec![0; self.images.len()];
+        for (i, x) in self.images.iter().enumerate() {
+            images[*x] = i;
+        }
+        Permutation { images }
+    }
+
+    /// Conjugate by the half twist, Δ⁻¹·x·Δ, which maps σ_i to σ_{n-i}.
+    pub fn flip(&self) -> Permutation {
+        let n = self.images.len();
+        Permutation {
+            images: self.images.iter().rev().map(|x| n - 1 - x).collect(),
+        }
+    }
+
+    /// The right complement x⁻¹·Δ, the simple element completing this one
+    /// to the half twist.
+    pub fn complement(&self) -> Permutation {
+        &self.inverse() * &Permutation::half_twist(self.images.len())
+    }
+
+    /// Bitmask of the generators σ_i, with bit `i` set, such that the simple
+    /// element ends in σ_i. This is the finishing set of the simple element.
+    pub fn right_descents(&self) -> u32 {
+        let mut res = 0;
+        for i in 1..self.images.len() {
+            if self.images[i - 1] > self.images[i] {
+                res |= 1 << i;
+            }
+        }
+        res
+    }
+
+    /// Bitmask of the generators σ_i, with bit `i` set, such that the simple
+    /// element starts with σ_i. This is the starting set of the simple element.
+    pub fn left_descents(&self) -> u32 {
+        self.inverse().right_descents()
+    }
+
+    /// A reduced positive word in the Artin generators, given by their
+    /// indices `1..n`, whose permutation braid is this permutation.
+    pub fn positive_word(&self) -> Vec<usize> {
+        let mut images = self.images.clone();
+        let mut word = Vec::new();
+        let mut i = 1;
+        while i < images.len() {
+            if images[i - 1] > images[i] {
+                images.swap(i - 1, i);
+                word.push(i);
+                i = 1;
+            } else {
+                i += 1;
+            }
+        }
+        word.reverse();
+        word
+    }
+}
+
+/// The permutation of the product of the permutation braids.
+impl Mul for &Permutation {
+    type Output = Permutation;
+
+    fn mul(self, other: &Permutation) -> Permutation {
+        Permutation {
+            images: other.images.iter().map(|x| self.images[*x]).collect(),
+        }
+    }
+}
+
+pub fn factorial(n: usize) -> usize {
+    (1..=n).product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_permutations_are_lexicographic() {
+        let perms = Permutation::all(4);
+        assert_eq!(perms.len(), 24);
+        assert_eq!(perms[0], Permutation::identity(4));
+        assert_eq!(perms[23].images, vec![3, 2, 1, 0]);
+        assert!(perms.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn from_rank_inverts_enumeration() {
+        for (rank, perm) in Permutation::all(5).into_iter().enumerate() {
+            assert_eq!(perm.rank(), rank);
+            assert_eq!(Permutation::from_rank(5, rank), perm);
+        }
+    }
+
+    #[test]
+    fn positive_word_is_reduced_word_of_permutation() {
+        for perm in Permutation::all(5) {
+            let word = perm.positive_word();
+            let mut images: Vec<usize> = (0..5).collect();
+            for i in &word {
+                images.swap(i - 1, *i);
+            }
+            assert_eq!(images, perm.images);
+            let inversions = (0..5)
+                .flat_map(|i| (i + 1..5).map(move |j| (i, j)))
+                .filter(|(i, j)| perm.images[*i] > perm.images[*j])
+                .count();
+            assert_eq!(word.len(), inversions);
+        }
+    }
+
+    #[test]
+    fn descents_of_half_twist() {
+        let delta = Permutation::all(4).pop().unwrap();
+        assert_eq!(delta.left_descents(), 0b1110);
+        assert_eq!(delta.right_descents(), 0b1110);
+        assert_eq!(delta.positive_word().len(), 6);
+        assert_eq!(delta, Permutation::half_twist(4));
+        assert!(delta.complement().is_identity());
+    }
+
+    #[test]
+    fn products_concatenate_words() {
+        let perms = Permutation::all(4);
+        for x in &perms {
+            for y in &perms {
+                let mut images = x.images.clone();
+                for i in y.positive_word() {
+                    images.swap(i - 1, i);
+                }
+                assert_eq!((x * y).images, images);
+            }
+            assert_eq!(&(x * &x.complement()), &Permutation::half_twist(4));
+            assert_eq!(x.flip().flip(), *x);
+        }
+        let generator = |i: usize| {
+            let mut res = Permutation::identity(4);
+            res.images.swap(i - 1, i);
+            res
+        };
+        for i in 1..4 {
+            assert_eq!(generator(i).flip(), generator(4 - i));
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! A bounded priority queue of items bucketed by a small integer key, which
+//! the searches use for their frontiers keyed by projlen. Lower keys are
+//! better: items are popped from the lowest bucket, and once the queue is
+//! full, new items evict items from the highest one.
+
+use hashbrown::HashMap;
+use rand::Rng;
+use rand_pcg::Pcg32;
+use std::collections::BTreeMap;
+
+/// How a queue chooses among items with the same key when it has to drop
+/// one of them.
+pub enum TieBreak {
+    /// Keep the items that arrived first: a new item with the highest key of
+    /// a full queue is dropped, and evictions take the newest item.
+    Lifo,
+    /// Keep a random sample of the items with the highest key of a full
+    /// queue, replacing kept items by reservoir sampling.
+    Random(Pcg32),
+    /// Keep at most `size` items of every key, sampled uniformly from those
+    /// pushed since the bucket was last empty.
+    Reservoir { size: usize, rng: Pcg32 },
+}
+
+/// Items bucketed by key, holding at most `capacity` of them. Within a
+/// bucket, items are popped in the reverse order of their arrival.
+pub struct BucketQueue<T> {
+    buckets: BTreeMap<u32, Vec<T>>,
+    len: usize,
+    capacity: usize,
+    tie_break: TieBreak,
+    /// Number of items pushed with every key, for the random tie-breaks.
+    seen: HashMap<u32, u64>,
+}
+
+impl<T> BucketQueue<T> {
+    pub fn new(capacity: usize, tie_break: TieBreak) -> BucketQueue<T> {
+        BucketQueue {
+            buckets: BTreeMap::new(),
+            len: 0,
+            capacity,
+            tie_break,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// An empty queue without a bound on its size.
+    pub fn unbounded() -> BucketQueue<T> {
+        Self::new(usize::MAX, TieBreak::Lifo)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn min_key(&self) -> Option<u32> {
+        self.buckets.keys().next().copied()
+    }
+
+    pub fn max_key(&self) -> Option<u32> {
+        self.buckets.keys().next_back().copied()
+    }
+
+    /// Whether `push` could keep an item with `key`. An item for which this
+    /// is false would be dropped, so callers can skip preparing it.
+    pub fn admits(&self, key: u32) -> bool {
+        if self.len < self.capacity {
+            return true;
+        }
+        match self.max_key() {
+            Some(worst) => key < worst || key == worst && !matches!(self.tie_break, TieBreak::Lifo),
+            None => false,
+        }
+    }
+
+    /// Adds `item` with `key`, evicting an item with the highest key if the
+    /// queue is full, and returns whether `item` was kept.
+    pub fn push(&mut self, key: u32, item: T) -> bool {
+        if !matches!(self.tie_break, TieBreak::Lifo) {
+            *self.seen.entry(key).or_default() += 1;
+        }
+        if let TieBreak::Reservoir { size, rng } = &mut self.tie_break {
+            if self.buckets.get(&key).map_or(0, |x| x.len()) >= *size {
+                let x = rng.gen_range(0..self.seen[&key]) as usize;
+                return match self.buckets.get_mut(&key) {
+                    Some(bucket) if x < bucket.len() => {
+                        bucket[x] = item;
+                        true
+                    }
+                    _ => false,
+                };
+            }
+        }
+        if self.len >= self.capacity {
+            let Some(mut worst) = self.buckets.last_entry() else {
+                return false;
+            };
+            let worst_key = *worst.key();
+            if key > worst_key {
+                return false;
+            }
+            let bucket = worst.get_mut();
+            if key == worst_key {
+                if let TieBreak::Random(rng) = &mut self.tie_break {
+                    let x = rng.gen_range(0..self.seen[&key]) as usize;
+                    if x < bucket.len() {
+                        bucket[x] = item;
+                        return true;
+                    }
+                }
+                return false;
+            }
+            match &mut self.tie_break {
+                TieBreak::Random(rng) => {
+                    bucket.swap_remove(rng.gen_range(0..bucket.len()));
+                }
+                _ => {
+                    bucket.pop();
+                }
+            }
+            if bucket.is_empty() {
+                self.seen.remove(&worst_key);
+                worst.remove();
+            }
+            self.len -= 1;
+        }
+        self.buckets.entry(key).or_default().push(item);
+        self.len += 1;
+        true
+    }
+
+    /// Removes the most recent item with the lowest key.
+    pub fn pop_min(&mut self) -> Option<(u32, T)> {
+        let (key, mut items) = self.pop_min_batch(1)?;
+        Some((key, items.pop()?))
+    }
+
+    /// Removes the `count` most recent items with the lowest key, or all of
+    /// them if there are fewer, and returns them in their order of arrival.
+    pub fn pop_min_batch(&mut self, count: usize) -> Option<(u32, Vec<T>)> {
+        let mut entry = self.buckets.first_entry()?;
+        let key = *entry.key();
+        let bucket = entry.get_mut();
+        let batch = bucket.split_off(bucket.len().saturating_sub(count));
+        if bucket.is_empty() {
+            entry.remove();
+            self.seen.remove(&key);
+        }
+        self.len -= batch.len();
+        Some((key, batch))
+    }
+
+    /// The items in order of increasing key, and in their order of arrival
+    /// within every bucket.
+    pub fn values(&self) -> impl Iterator<Item = &T> + Clone {
+        self.buckets.values().flatten()
+    }
+
+    /// The items in the order of `values`.
+    pub fn into_values(self) -> impl Iterator<Item = T> {
+        self.buckets.into_values().flatten()
+    }
+
+    /// Pushes the items of `other` in the order of `values`.
+    pub fn append(&mut self, other: BucketQueue<T>) {
+        for (key, bucket) in other.buckets {
+            for item in bucket {
+                self.push(key, item);
+            }
+        }
+    }
+
+    /// The number of items with every key.
+    pub fn histogram(&self) -> BTreeMap<u32, usize> {
+        self.buckets.iter().map(|(key, bucket)| (*key, bucket.len())).collect()
+    }
+}
+
+/// Pushes the items in order, so that extending an empty queue by more items
+/// than it holds keeps the first ones of the lowest keys.
+impl<T> Extend<(u32, T)> for BucketQueue<T> {
+    fn extend<I: IntoIterator<Item = (u32, T)>>(&mut self, iter: I) {
+        for (key, item) in iter {
+            self.push(key, item);
+        }
+    }
+}
+
+/// Collects the items into an unbounded queue.
+impl<T> FromIterator<(u32, T)> for BucketQueue<T> {
+    fn from_iter<I: IntoIterator<Item = (u32, T)>>(iter: I) -> BucketQueue<T> {
+        let mut res = BucketQueue::unbounded();
+        res.extend(iter);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn full_queues_keep_the_lowest_keys() {
+        let mut queue = BucketQueue::new(4, TieBreak::Lifo);
+        queue.extend([(5, 'a'), (3, 'b'), (5, 'c'), (7, 'd')]);
+        assert!(!queue.admits(8));
+        assert!(queue.admits(6));
+        assert!(!queue.push(7, 'e'));
+        assert!(queue.push(3, 'f'));
+        assert!(queue.push(4, 'g'));
+        assert!(!queue.push(5, 'h'));
+        assert_eq!(queue.values().collect::<String>(), "bfga");
+        assert_eq!(queue.histogram(), BTreeMap::from([(3, 2), (4, 1), (5, 1)]));
+        assert_eq!((queue.min_key(), queue.max_key()), (Some(3), Some(5)));
+        assert_eq!(queue.pop_min(), Some((3, 'f')));
+        assert_eq!(queue.pop_min_batch(3), Some((3, vec!['b'])));
+        assert_eq!(queue.pop_min_batch(3), Some((4, vec!['g'])));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.into_values().collect::<String>(), "a");
+
+        let mut empty: BucketQueue<char> = BucketQueue::new(0, TieBreak::Lifo);
+        assert!(!empty.push(1, 'a'));
+        assert_eq!(empty.pop_min(), None);
+        assert_eq!(empty.pop_min_batch(1), None);
+    }
+
+    #[test]
+    fn random_tie_breaks_sample_the_highest_key() {
+        let mut queue = BucketQueue::new(10, TieBreak::Random(Pcg32::seed_from_u64(0)));
+        queue.extend((0..1000).map(|x| (x % 2, x)));
+        assert_eq!(queue.histogram(), BTreeMap::from([(0, 10)]));
+        // The kept items are spread over all those pushed, not the first or
+        // last ones.
+        assert!(queue.values().any(|x| *x < 500) && queue.values().any(|x| *x >= 500));
+
+        let tie_break = TieBreak::Reservoir {
+            size: 3,
+            rng: Pcg32::seed_from_u64(0),
+        };
+        let mut queue = BucketQueue::new(usize::MAX, tie_break);
+        queue.extend((0..1000).map(|x| (x % 2, x)));
+        assert_eq!(queue.histogram(), BTreeMap::from([(0, 3), (1, 3)]));
+        while let Some((key, x)) = queue.pop_min() {
+            assert_eq!(x % 2, key);
+        }
+        assert!(queue.is_empty());
+    }
+}
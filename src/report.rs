@@ -0,0 +1,184 @@
+//! Output of the searches. By default, searches print their progress as text
+//! meant for humans. In JSON Lines mode, that text is suppressed, and every
+//! event of interest is instead printed as one JSON object per line, with
+//! its kind in the field `event`.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON_LINES: AtomicBool = AtomicBool::new(false);
+
+/// Switches between JSON Lines and human-readable output.
+pub fn set_json_lines(on: bool) {
+    JSON_LINES.store(on, Ordering::Relaxed);
+}
+
+pub fn json_lines() -> bool {
+    JSON_LINES.load(Ordering::Relaxed)
+}
+
+/// Prints a line of human-readable output, unless the output is JSON Lines.
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::report::json_lines() {
+            println!($($arg)*);
+        }
+    };
+}
+
+fn quote(s: &str) -> String {
+    let mut res = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(res, "\\u{:04x}", c as u32).unwrap(),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// A JSON object describing one event, built field by field.
+pub struct Event {
+    line: String,
+}
+
+impl Event {
+    pub fn new(kind: &str) -> Event {
+        Event {
+            line: format!("{{\"event\":{}", quote(kind)),
+        }
+    }
+
+    fn key(&mut self, key: &str) {
+        write!(self.line, ",{}:", quote(key)).unwrap();
+    }
+
+    /// Adds a number or boolean.
+    pub fn field(mut self, key: &str, value: impl Display) -> Event {
+        self.key(key);
+        write!(self.line, "{}", value).unwrap();
+        self
+    }
+
+    /// Adds a number or boolean, or null if there is none.
+    pub fn maybe(self, key: &str, value: Option<impl Display>) -> Event {
+        match value {
+            Some(value) => self.field(key, value),
+            None => self.field(key, "null"),
+        }
+    }
+
+    /// Adds a number of seconds, or of something per second.
+    pub fn float(self, key: &str, value: f64) -> Event {
+        self.field(
+            key,
+            format!("{:.3}", if value.is_finite() { value } else { 0.0 }),
+        )
+    }
+
+    pub fn string(mut self, key: &str, value: &str) -> Event {
+        self.key(key);
+        self.line.push_str(&quote(value));
+        self
+    }
+
+    /// Adds a list of numbers.
+    pub fn list<T: Display>(mut self, key: &str, values: &[T]) -> Event {
+        self.key(key);
+        self.line.push('[');
+        for (i, x) in values.iter().enumerate() {
+            if i > 0 {
+                self.line.push(',');
+            }
+            write!(self.line, "{}", x).unwrap();
+        }
+        self.line.push(']');
+        self
+    }
+
+    /// Adds the number of states of every projlen, as an object whose keys
+    /// are the projlens.
+    pub fn histogram(mut self, key: &str, histogram: &BTreeMap<u32, usize>) -> Event {
+        self.key(key);
+        self.line.push('{');
+        for (i, (projlen, count)) in histogram.iter().enumerate() {
+            if i > 0 {
+                self.line.push(',');
+            }
+            write!(self.line, "\"{}\":{}", projlen, count).unwrap();
+        }
+        self.line.push('}');
+        self
+    }
+
+    /// Adds an object of further fields, given as already encoded JSON values.
+    pub fn object(mut self, key: &str, fields: &[(&str, String)]) -> Event {
+        self.key(key);
+        self.line.push('{');
+        for (i, (name, value)) in fields.iter().enumerate() {
+            if i > 0 {
+                self.line.push(',');
+            }
+            write!(self.line, "{}:{}", quote(name), value).unwrap();
+        }
+        self.line.push('}');
+        self
+    }
+
+    pub fn render(mut self) -> String {
+        self.line.push('}');
+        self.line
+    }
+
+    /// Prints the event if the output is JSON Lines.
+    pub fn emit(self) {
+        if json_lines() {
+            println!("{}", self.render());
+        }
+    }
+}
+
+/// Encodes a string as a JSON value, for use with `Event::object`.
+pub fn json_string(s: &str) -> String {
+    quote(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_json_objects() {
+        let histogram = BTreeMap::from([(3, 10), (4, 2)]);
+        let event = Event::new("layer")
+            .field("layer", 7)
+            .maybe("seed", None::<u64>)
+            .float("elapsed_secs", 1.5)
+            .list("factors", &[13, 10])
+            .histogram("histogram", &histogram)
+            .object(
+                "parameters",
+                &[("strategy", json_string("beam")), ("keep", "5".to_string())],
+            );
+        assert_eq!(
+            event.render(),
+            "{\"event\":\"layer\",\"layer\":7,\"seed\":null,\"elapsed_secs\":1.500,\"factors\":[13,10],\
+             \"histogram\":{\"3\":10,\"4\":2},\"parameters\":{\"strategy\":\"beam\",\"keep\":5}}"
+        );
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        let event = Event::new("a\"b").string("path", "c:\\d\n");
+        assert_eq!(
+            event.render(),
+            "{\"event\":\"a\\\"b\",\"path\":\"c:\\\\d\\n\"}"
+        );
+    }
+}
@@ -0,0 +1,739 @@
+//! Search strategies for braids whose Burau image is scalar. Every strategy
+//! starts from a [`Frontier`] of states bucketed by projlen, prints its
+//! progress, and records the kernel elements it finds in the [`Run`] until
+//! that has enough of them or runs out of time, or the frontier runs out of
+//! states.
+
+use hashbrown::HashMap;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use crate::algebra::{DenseMatrix, Span};
+use crate::checkpoint::Checkpoints;
+use crate::findings::Findings;
+use crate::garside::{act_by_dense, generate_descendants, num_factors, ActionTable};
+use crate::progress;
+use crate::queue::{BucketQueue, TieBreak};
+use crate::report::Event;
+use crate::transposition::{entry_key, TranspositionTable};
+use crate::tree::Path;
+
+/// Number of states expanded between progress reports of the strategies
+/// that do not proceed in layers.
+const PROGRESS_INTERVAL: usize = 100000;
+
+/// Everything a search updates besides its frontier.
+pub struct Run {
+    pub transpositions: TranspositionTable,
+    pub checkpoints: Checkpoints,
+    pub findings: Findings,
+    last_report: Instant,
+}
+
+impl Run {
+    pub fn new(transpositions: TranspositionTable, checkpoints: Checkpoints, findings: Findings) -> Run {
+        Run {
+            transpositions,
+            checkpoints,
+            findings,
+            last_report: Instant::now(),
+        }
+    }
+
+    /// Reports the end of a layer in which `expanded` states were expanded,
+    /// leaving the frontier `states`, and passes it on to the checkpoints.
+    pub fn finish_layer<'a, I>(&mut self, layer_seed: u64, expanded: usize, states: I)
+    where
+        I: IntoIterator<Item = &'a State> + Clone,
+    {
+        self.report("layer", expanded, states.clone());
+        self.checkpoints.finish_layer(layer_seed, states);
+    }
+
+    /// Reports the progress of a strategy that does not proceed in layers,
+    /// after it has expanded `expanded` states since the last report.
+    pub fn report_progress<'a>(&mut self, expanded: usize, states: impl IntoIterator<Item = &'a State>) {
+        self.report("progress", expanded, states);
+    }
+
+    /// Records the kernel element `state` in the findings, printing it unless
+    /// it is equivalent to one found before, and returns whether the search
+    /// should stop.
+    pub fn record(&mut self, state: &State, layer: Option<u64>) -> bool {
+        let factors = state.factors.to_vec();
+        if !self.findings.is_known(&factors, 0) {
+            progress!("Found kernel element. Garside generators:");
+            progress!("{:?}", state.factors);
+        }
+        self.findings.record(&factors, 0, layer)
+    }
+
+    /// Adds `state` to `frontier`, unless the frontier would drop it or its
+    /// projective image has been reached before. The image is only recorded
+    /// once the frontier has kept the state, so that a state dropped by a
+    /// tie-break does not block later ones reaching the same image.
+    pub fn push(&mut self, frontier: &mut Frontier, state: State) {
+        let projlen = state.projlen();
+        if !frontier.admits(projlen) {
+            return;
+        }
+        if !self.transpositions.is_enabled() {
+            frontier.push(projlen, state);
+            return;
+        }
+        let key = entry_key(&state.mat, state.factors.last());
+        if !self.transpositions.contains_key(key) && frontier.push(projlen, state) {
+            self.transpositions.insert_key(key);
+        }
+    }
+
+    fn report<'a>(&mut self, kind: &str, expanded: usize, states: impl IntoIterator<Item = &'a State>) {
+        let secs = self.last_report.elapsed().as_secs_f64();
+        self.last_report = Instant::now();
+        if !crate::report::json_lines() {
+            return;
+        }
+        let mut histogram: BTreeMap<u32, usize> = BTreeMap::new();
+        for state in states {
+            *histogram.entry(state.projlen()).or_default() += 1;
+        }
+        let mut event = Event::new(kind);
+        if kind == "layer" {
+            event = event.field("layer", self.checkpoints.layer + 1);
+        }
+        event
+            .field("expanded", expanded)
+            .float("secs", secs)
+            .float("states_per_sec", expanded as f64 / secs)
+            .float("elapsed_secs", self.findings.elapsed().as_secs_f64())
+            .field("frontier", histogram.values().sum::<usize>())
+            .histogram("histogram", &histogram)
+            .emit();
+    }
+}
+
+/// A frontier of states bucketed by projlen.
+pub type Frontier = BucketQueue<State>;
+
+/// The states given by the single factors of B_n, bucketed by projlen.
+pub fn initial_states(table: &ActionTable, p: u32) -> Frontier {
+    bucket_by_projlen((1..=num_factors(table.n)).map(|x| State::new(x, table, p)))
+}
+
+/// Buckets `states` by projlen, keeping their order within every bucket.
+pub fn bucket_by_projlen(states: impl IntoIterator<Item = State>) -> Frontier {
+    states.into_iter().map(|x| (x.projlen(), x)).collect()
+}
+
+/// Prints the number of states of every projlen in `frontier`.
+fn print_histogram(frontier: &Frontier) {
+    for (projlen, count) in frontier.histogram() {
+        progress!("{}: {}", projlen, count);
+    }
+}
+
+/// Prints that a search stops because its frontier has run out of states.
+pub fn report_exhausted() {
+    progress!("The frontier is empty, so the search stops.");
+}
+
+/// Best projlen among all continuations of `candidate` by `depth` factors.
+fn evaluate_candidate(
+    candidate: &State,
+    descendants: &HashMap<u32, Vec<u32>>,
+    table: &ActionTable,
+    depth: usize,
+) -> u32 {
+    let mut best_projlen = u32::MAX;
+    let mut layer: Vec<State> = vec![candidate.clone()];
+    for layer_num in 0..depth {
+        let mut new_layer: Vec<State> = Vec::new();
+        for test_state in layer {
+            let last_factor = test_state.factors.last();
+            for descendant in &descendants[&last_factor] {
+                let new_state = test_state.append(*descendant, table);
+                if layer_num == depth - 1 {
+                    let this_projlen = new_state.projlen();
+                    if this_projlen < best_projlen {
+                        best_projlen = this_projlen;
+                    }
+                }
+                new_layer.push(new_state);
+            }
+        }
+        layer = new_layer;
+    }
+    best_projlen
+}
+
+/// Best projlen among `samples` random continuations of `candidate` by
+/// `depth` factors.
+fn evaluate_candidate_mc(
+    candidate: &State,
+    descendants: &HashMap<u32, Vec<u32>>,
+    table: &ActionTable,
+    depth: usize,
+    samples: usize,
+    seed: u64,
+) -> u32 {
+    let mut rng: rand_pcg::Lcg64Xsh32 = Pcg32::seed_from_u64(seed);
+    let mut best_projlen = u32::MAX;
+    // The walks only need the matrices, which alternate between two buffers.
+    let mut mat = candidate.mat.clone();
+    let mut next = candidate.mat.clone();
+    for _ in 0..samples {
+        mat.clone_from(&candidate.mat);
+        let mut span = candidate.span;
+        let mut last_factor = candidate.factors.last();
+        for _ in 0..depth {
+            let desc = &descendants[&last_factor];
+            let index = rng.gen_range(0..desc.len());
+            last_factor = desc[index];
+            span = act_by_dense(&mat, last_factor, table, &mut next).unwrap();
+            std::mem::swap(&mut mat, &mut next);
+        }
+        if span.projlen() < best_projlen {
+            best_projlen = span.projlen()
+        }
+    }
+    best_projlen
+}
+
+fn evaluate_candidates(
+    states: &[State],
+    descendants: &HashMap<u32, Vec<u32>>,
+    table: &ActionTable,
+    depth: usize,
+    samples: Option<usize>,
+    seed: u64,
+) -> Vec<u32> {
+    states
+        .iter()
+        .map(|state| match samples {
+            None => evaluate_candidate(state, descendants, table, depth),
+            Some(samples) => evaluate_candidate_mc(state, descendants, table, depth, samples, seed),
+        })
+        .collect()
+}
+
+/// Parameters of `lookahead_search`.
+#[derive(Clone, Debug)]
+pub struct Lookahead {
+    /// Candidates expanded in every layer.
+    pub width: usize,
+    /// Number of factors by which continuations of a candidate extend it.
+    pub depth: usize,
+    /// If given, continuations are sampled by this many random walks instead
+    /// of being enumerated.
+    pub samples: Option<usize>,
+}
+
+/// Beam search keeping the candidates whose continuations reach the lowest
+/// projlen.
+pub fn lookahead_search(
+    mut candidates: Vec<State>,
+    seed: u64,
+    lookahead: &Lookahead,
+    num_threads: usize,
+    run: &mut Run,
+    n: usize,
+) {
+    let descendants = generate_descendants(n);
+    let table = ActionTable::new(n);
+    let candidates_to_choose: usize = lookahead.width;
+    let depth = lookahead.depth;
+    let samples = lookahead.samples;
+    while !run.findings.is_done() {
+        if candidates.is_empty() {
+            report_exhausted();
+            return;
+        }
+        let layer = run.checkpoints.layer + 1;
+        let mut rng = Pcg32::seed_from_u64(run.checkpoints.layer_seed);
+        let chunks: Vec<&[State]> = candidates.chunks(num_threads).collect();
+        let evaluations: Vec<u32> = {
+            chunks
+                .into_par_iter()
+                .map(|candidates| {
+                    evaluate_candidates(candidates, &descendants, &table, depth, samples, seed)
+                })
+                .flatten()
+                .collect()
+        };
+
+        let mut all_evals: Vec<(&u32, State)> = evaluations.iter().zip(candidates).collect();
+        all_evals.sort_by_key(|(eval, _)| *eval);
+        progress!(
+            "Layer {}. Selected candidate with projlen {}. Best seen is {}.",
+            layer,
+            all_evals[0].1.projlen(),
+            all_evals[0].0
+        );
+        let mut new_candidates: Vec<State> = Vec::new();
+        let candidates_to_keep = if candidates_to_choose > evaluations.len() {
+            evaluations.len()
+        } else {
+            candidates_to_choose
+        };
+        for (_, best_candidate) in all_evals.drain(..candidates_to_keep) {
+            let last_factor = best_candidate.factors.last();
+            for descendant in &descendants[&last_factor] {
+                let new_state = best_candidate.append(*descendant, &table);
+                if new_state.projlen() == 1 && report_goal(&new_state) {
+                    if run.record(&new_state, Some(layer)) {
+                        return;
+                    }
+                    continue;
+                }
+                if run.transpositions.insert(&new_state.mat, *descendant) {
+                    new_candidates.push(new_state);
+                }
+            }
+        }
+        // Every continuation may have been dropped, in which case the next
+        // layer finds the frontier empty.
+        if !new_candidates.is_empty() {
+            let rot = rng.gen_range(0..new_candidates.len());
+            new_candidates.rotate_left(rot);
+        }
+        candidates = new_candidates;
+        run.finish_layer(rng.gen(), candidates_to_keep, &candidates);
+    }
+}
+
+/// Expands `states` by one factor, returning the kernel elements found and
+/// the other new states.
+fn run_to_fixed_limited(
+    states: &[State],
+    descendants: &HashMap<u32, Vec<u32>>,
+    table: &ActionTable,
+) -> (Vec<State>, Vec<State>) {
+    let mut found = Vec::new();
+    let mut result = Vec::new();
+    for state in states {
+        let last_factor = state.factors.last();
+
+        for descendant in &descendants[&last_factor] {
+            let new_state = state.append(*descendant, table);
+            if new_state.projlen() == 1 && report_goal(&new_state) {
+                found.push(new_state);
+                continue;
+            }
+            result.push(new_state);
+        }
+    }
+    (found, result)
+}
+
+/// Best-first search, expanding up to `tohandle` states of the lowest projlen
+/// at a time in parallel chunks of `num_threads` states, and keeping at most
+/// `todo` states in the frontier.
+pub fn search_best_first_parallel(
+    states: Frontier,
+    num_threads: usize,
+    todo: usize,
+    tohandle: usize,
+    run: &mut Run,
+    n: usize,
+) {
+    let descendants = generate_descendants(n);
+    let table = ActionTable::new(n);
+    // Sufficient values of todo:
+    // p = 2: 5
+    // p = 3: 5000
+    // p = 5: 150000
+    let mut frontier = Frontier::new(todo, TieBreak::Lifo);
+    frontier.append(states);
+    while !run.findings.is_done() {
+        let layer = run.checkpoints.layer + 1;
+        progress!(
+            "Finished layer {}. Projlen distribution for next layer:",
+            layer
+        );
+        print_histogram(&frontier);
+        let Some((current_projlen, states_to_handle)) = frontier.pop_min_batch(tohandle) else {
+            report_exhausted();
+            return;
+        };
+        progress!("Handling layer {}", current_projlen);
+
+        let chunks: Vec<&[State]> = states_to_handle.chunks(num_threads).collect();
+        let results: Vec<_> = chunks
+            .into_par_iter()
+            .map(|chunk| run_to_fixed_limited(chunk, &descendants, &table))
+            .collect();
+        for (found, new_states) in results {
+            for found in found {
+                if run.record(&found, Some(layer)) {
+                    return;
+                }
+            }
+            for new_state in new_states {
+                run.push(&mut frontier, new_state);
+            }
+        }
+        run.finish_layer(0, states_to_handle.len(), frontier.values());
+    }
+}
+
+/// Beam search expanding the `to_handle_per_layer` states of lowest projlen in
+/// every layer, in parallel chunks of `num_threads` states.
+pub fn beam_search_parallel(
+    states: Frontier,
+    num_threads: usize,
+    to_handle_per_layer: usize,
+    run: &mut Run,
+    n: usize,
+) {
+    let descendants = generate_descendants(n);
+    let table = ActionTable::new(n);
+    let mut frontier = Frontier::new(to_handle_per_layer, TieBreak::Lifo);
+    frontier.append(states);
+    while !run.findings.is_done() {
+        if frontier.is_empty() {
+            report_exhausted();
+            return;
+        }
+        let mut have_handled: usize = 0;
+        let mut next = Frontier::new(to_handle_per_layer, TieBreak::Lifo);
+        let layer = run.checkpoints.layer + 1;
+        progress!("Layer {}. Truncated elements:", layer);
+
+        while let Some((projlen, states_to_handle)) = frontier.pop_min_batch(usize::MAX) {
+            let chunks: Vec<&[State]> = states_to_handle.chunks(num_threads).collect();
+            let results: Vec<_> = chunks
+                .into_par_iter()
+                .map(|chunk| run_to_fixed_limited(chunk, &descendants, &table))
+                .collect();
+
+            for (found, new_states) in results {
+                for found in found {
+                    if run.record(&found, Some(layer)) {
+                        return;
+                    }
+                }
+                for new_state in new_states {
+                    run.push(&mut next, new_state);
+                }
+            }
+
+            have_handled += states_to_handle.len();
+            progress!("{}: {}", projlen, states_to_handle.len());
+        }
+
+        frontier = next;
+        run.finish_layer(0, have_handled, frontier.values());
+    }
+}
+
+/// Beam search keeping the `beam_width` states of lowest projlen in every
+/// layer, breaking ties at the highest kept projlen at random.
+pub fn beam_search(
+    states: Frontier,
+    beam_width: usize,
+    run: &mut Run,
+    n: usize,
+) {
+    let descendants = generate_descendants(n);
+    let table = ActionTable::new(n);
+    let mut frontier = states;
+
+    while !run.findings.is_done() {
+        if frontier.is_empty() {
+            report_exhausted();
+            return;
+        }
+        let layer = run.checkpoints.layer + 1;
+        let mut rng = Pcg32::seed_from_u64(run.checkpoints.layer_seed);
+        let layer_seed = rng.gen();
+        let mut next_layer = Frontier::new(beam_width, TieBreak::Random(rng));
+
+        for state in frontier.values() {
+            let last_factor = state.factors.last();
+
+            for descendant in &descendants[&last_factor] {
+                let new_state = state.append(*descendant, &table);
+                if new_state.projlen() == 1 && report_goal(&new_state) {
+                    if run.record(&new_state, Some(layer)) {
+                        return;
+                    }
+                    continue;
+                }
+                run.push(&mut next_layer, new_state);
+            }
+        }
+        let expanded = frontier.len();
+        frontier = next_layer;
+        run.finish_layer(layer_seed, expanded, frontier.values());
+        progress!(
+            "Finished layer {}. Projlen distribution for next layer:",
+            layer
+        );
+        print_histogram(&frontier);
+    }
+}
+
+/// Best-first search expanding one state at a time, keeping at most
+/// `max_keep` states in the frontier.
+pub fn search_best_first_limited_width(
+    states: Frontier,
+    max_keep: usize,
+    run: &mut Run,
+    n: usize,
+) {
+    let descendants = generate_descendants(n);
+    let table = ActionTable::new(n);
+    let mut frontier = Frontier::new(max_keep, TieBreak::Lifo);
+    frontier.append(states);
+    let mut highest_seen_projlen = u32::MIN;
+    let mut expanded = 0;
+
+    while !run.findings.is_done() {
+        expanded += 1;
+        if expanded % PROGRESS_INTERVAL == 0 {
+            run.report_progress(PROGRESS_INTERVAL, frontier.values());
+        }
+        let Some((lowest, state)) = frontier.pop_min() else {
+            report_exhausted();
+            return;
+        };
+        if lowest > highest_seen_projlen {
+            progress!("Now considering elements with projlen {}", lowest);
+            highest_seen_projlen = lowest;
+        }
+
+        let last_factor = state.factors.last();
+
+        for descendant in &descendants[&last_factor] {
+            let new_state = state.append(*descendant, &table);
+            if new_state.projlen() == 1 && report_goal(&new_state) {
+                if run.record(&new_state, None) {
+                    return;
+                }
+                continue;
+            }
+            run.push(&mut frontier, new_state);
+        }
+    }
+}
+
+/// Best-first search expanding one state at a time, keeping a random sample
+/// of at most `reservoir_size` of the states reached with every projlen.
+pub fn search_best_first_reservoir(
+    states: Frontier,
+    seed: u64,
+    reservoir_size: usize,
+    run: &mut Run,
+    n: usize,
+) {
+    let descendants = generate_descendants(n);
+    let table = ActionTable::new(n);
+    let tie_break = TieBreak::Reservoir {
+        size: reservoir_size,
+        rng: Pcg32::seed_from_u64(seed),
+    };
+    let mut frontier = Frontier::new(usize::MAX, tie_break);
+    frontier.append(states);
+    let mut expanded = 0;
+
+    while !run.findings.is_done() {
+        expanded += 1;
+        if expanded % PROGRESS_INTERVAL == 0 {
+            run.report_progress(PROGRESS_INTERVAL, frontier.values());
+        }
+        let Some((_, state)) = frontier.pop_min() else {
+            report_exhausted();
+            return;
+        };
+
+        let last_factor = state.factors.last();
+
+        for descendant in &descendants[&last_factor] {
+            let new_state = state.append(*descendant, &table);
+            if new_state.projlen() == 1 && report_goal(&new_state) {
+                if run.record(&new_state, None) {
+                    return;
+                }
+                continue;
+            }
+            run.push(&mut frontier, new_state);
+        }
+    }
+}
+
+/// Returns whether `state` is a kernel element, and prints it if it has
+/// projlen 1 without being one. Kernel elements are printed by
+/// [`Run::record`] once the findings have seen them, as the parallel
+/// strategies test their states in worker threads.
+pub fn report_goal(state: &State) -> bool {
+    match state.goal() {
+        Goal::Scalar => true,
+        Goal::NonScalar => {
+            progress!("Found non-scalar Burau image with projlen 1. Garside generators:");
+            progress!("{:?}", state.factors);
+            Event::new("non_scalar").list("factors", &state.factors.to_vec()).emit();
+            false
+        }
+        Goal::Unreached => false,
+    }
+}
+
+/// Outcome of the goal test on a state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// The Burau image has projlen greater than 1.
+    Unreached,
+    /// The Burau image has projlen 1 but is not of the form c·v^k·I.
+    NonScalar,
+    /// The Burau image is c·v^k·I, so the braid is a kernel element.
+    Scalar,
+}
+
+/// A braid given by its Garside factors, together with its Burau image and
+/// the span of powers of the image, which is kept so that the projlen used
+/// throughout the searches is never recomputed.
+#[derive(Clone)]
+pub struct State {
+    pub factors: Path,
+    pub mat: DenseMatrix,
+    pub span: Span,
+}
+
+impl State {
+    pub fn new(factor: u32, table: &ActionTable, p: u32) -> State {
+        let eye = DenseMatrix::identity(table.n - 1, p);
+        let mut mat = DenseMatrix::zero(table.n - 1, p);
+        let span = act_by_dense(&eye, factor, table, &mut mat).unwrap();
+        State {
+            factors: Path::new(factor),
+            mat,
+            span,
+        }
+    }
+
+    /// The state of the braid with the given factors, which must be non-empty.
+    pub fn from_factors(factors: &[u32], table: &ActionTable, p: u32) -> State {
+        let mut res = State::new(factors[0], table, p);
+        for factor in &factors[1..] {
+            res = res.append(*factor, table);
+        }
+        res
+    }
+
+    pub fn projlen(&self) -> u32 {
+        self.span.projlen()
+    }
+
+    pub fn goal(&self) -> Goal {
+        if self.projlen() > 1 {
+            Goal::Unreached
+        } else if self.mat.is_scalar() {
+            Goal::Scalar
+        } else {
+            Goal::NonScalar
+        }
+    }
+
+    pub fn is_goal(&self) -> bool {
+        self.goal() == Goal::Scalar
+    }
+
+    pub fn append(&self, factor: u32, table: &ActionTable) -> State {
+        let factors = self.factors.push(factor);
+        let mut mat = DenseMatrix::zero(self.mat.dim, self.mat.p);
+        let span = act_by_dense(&self.mat, factor, table, &mut mat).unwrap();
+        State { factors, mat, span }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::Matrix;
+    use crate::transposition::TranspositionTable;
+    use std::time::Duration;
+
+    #[test]
+    fn states_keep_the_span_of_their_image() {
+        let table = ActionTable::new(4);
+        let descendants = generate_descendants(4);
+        for p in [2, 3, 5] {
+            let mut state = State::new(13, &table, p);
+            for _ in 0..30 {
+                let factor = descendants[&state.factors.last()][0];
+                state = state.append(factor, &table);
+                let mat = Matrix::from(&state.mat);
+                let nonzero = || mat.d.iter().filter(|x| !x.is_zero());
+                assert_eq!(state.span.min_power, nonzero().map(|x| x.min_power()).min().unwrap());
+                assert_eq!(state.span.max_power, nonzero().map(|x| x.max_power()).max().unwrap());
+                assert_eq!(state.projlen(), mat.projlen());
+            }
+        }
+    }
+
+    #[test]
+    fn searches_stop_when_the_frontier_is_empty() {
+        let run = || {
+            Run::new(
+                TranspositionTable::new(0),
+                Checkpoints::new(None, Duration::ZERO, "beam", 2, 4, 0),
+                Findings::new(1, None, 2, 4, 0),
+            )
+        };
+        search_best_first_parallel(Frontier::unbounded(), 16, 10, 10, &mut run(), 4);
+        beam_search_parallel(Frontier::unbounded(), 16, 10, &mut run(), 4);
+        beam_search(Frontier::unbounded(), 10, &mut run(), 4);
+        search_best_first_limited_width(Frontier::unbounded(), 10, &mut run(), 4);
+        search_best_first_reservoir(Frontier::unbounded(), 0, 10, &mut run(), 4);
+        let lookahead = Lookahead {
+            width: 10,
+            depth: 1,
+            samples: None,
+        };
+        lookahead_search(Vec::new(), 0, &lookahead, 16, &mut run(), 4);
+    }
+
+    #[test]
+    fn lookahead_stops_when_transpositions_empty_its_frontier() {
+        let table = ActionTable::new(4);
+        let mut run = Run::new(
+            TranspositionTable::new(1 << 20),
+            Checkpoints::new(None, Duration::ZERO, "lookahead", 2, 4, 0),
+            Findings::new(1000, None, 2, 4, 0),
+        );
+        let lookahead = Lookahead {
+            width: 1,
+            depth: 2,
+            samples: None,
+        };
+        let candidates = initial_states(&table, 2).into_values().collect();
+        lookahead_search(candidates, 0, &lookahead, 16, &mut run, 4);
+        assert!(run.checkpoints.layer > 1);
+        assert!(!run.findings.is_done());
+    }
+
+    #[test]
+    fn images_of_dropped_states_are_not_recorded() {
+        let table = ActionTable::new(4);
+        let mut run = Run::new(
+            TranspositionTable::new(1 << 20),
+            Checkpoints::new(None, Duration::ZERO, "reservoir", 5, 4, 0),
+            Findings::new(1, None, 5, 4, 0),
+        );
+        let tie_break = TieBreak::Reservoir {
+            size: 0,
+            rng: Pcg32::seed_from_u64(0),
+        };
+        let mut full = Frontier::new(usize::MAX, tie_break);
+        let state = State::new(13, &table, 5);
+        run.push(&mut full, state.clone());
+        assert!(full.is_empty());
+        let mut open = Frontier::unbounded();
+        run.push(&mut open, state.clone());
+        run.push(&mut open, state);
+        assert_eq!(open.len(), 1);
+    }
+}
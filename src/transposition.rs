@@ -0,0 +1,145 @@
+use hashbrown::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::algebra::DenseMatrix;
+
+/// Approximate memory used by one entry of the table, including the
+/// overhead of the hash set.
+const BYTES_PER_ENTRY: usize = 16;
+
+/// A bounded set of the projective Burau images seen so far, used to drop
+/// states whose image has already been reached by another factor sequence.
+/// Since the factors that may follow a state depend on its last factor, an
+/// image is recorded together with the last factor reaching it.
+///
+/// Images are stored as 64-bit fingerprints of their canonical form, so a
+/// fingerprint collision may occasionally drop a new state. Entries live in
+/// two generations of equal size: when the current one is full, it replaces
+/// the previous one, and the oldest entries are forgotten.
+pub struct TranspositionTable {
+    current: HashSet<u64>,
+    previous: HashSet<u64>,
+    capacity: usize,
+}
+
+impl TranspositionTable {
+    /// A table using at most about `memory` bytes. A table with no memory
+    /// treats every image as new.
+    pub fn new(memory: usize) -> TranspositionTable {
+        TranspositionTable {
+            current: HashSet::new(),
+            previous: HashSet::new(),
+            capacity: memory / BYTES_PER_ENTRY / 2,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Records the projective image `mat` reached with last factor
+    /// `last_factor`, and returns whether the pair was new.
+    pub fn insert(&mut self, mat: &DenseMatrix, last_factor: u32) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        self.insert_key(entry_key(mat, last_factor))
+    }
+
+    /// Whether the pair with the given `entry_key` has been recorded. A table
+    /// with no memory contains nothing.
+    pub fn contains_key(&self, key: u64) -> bool {
+        self.previous.contains(&key) || self.current.contains(&key)
+    }
+
+    /// Records the pair with the given `entry_key`, and returns whether it
+    /// was new.
+    pub fn insert_key(&mut self, key: u64) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        if self.previous.contains(&key) || !self.current.insert(key) {
+            return false;
+        }
+        if self.current.len() >= self.capacity {
+            self.previous = std::mem::take(&mut self.current);
+        }
+        true
+    }
+}
+
+/// The key under which the table records the projective image `mat` reached
+/// with last factor `last_factor`.
+pub fn entry_key(mat: &DenseMatrix, last_factor: u32) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (fingerprint(mat), last_factor).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A hash of the canonical projective form of `mat`.
+pub fn fingerprint(mat: &DenseMatrix) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    mat.canonical().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::{Matrix, Polynomial};
+
+    fn scalar(power: i32, p: u32) -> DenseMatrix {
+        let mut mat = Matrix::identity(3, p);
+        for i in 0..3 {
+            mat.d[i * 3 + i] = Polynomial::new(vec![(power, 2)], p);
+        }
+        DenseMatrix::from(&mat)
+    }
+
+    #[test]
+    fn duplicates_are_rejected() {
+        let mut table = TranspositionTable::new(1 << 20);
+        assert!(table.insert(&DenseMatrix::identity(3, 5), 1));
+        assert!(!table.insert(&DenseMatrix::identity(3, 5), 1));
+        assert!(!table.insert(&scalar(4, 5), 1));
+        assert!(table.insert(&DenseMatrix::identity(3, 5), 2));
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn disabled_table_accepts_everything() {
+        let mut table = TranspositionTable::new(0);
+        assert!(table.insert(&DenseMatrix::identity(3, 5), 1));
+        assert!(table.insert(&DenseMatrix::identity(3, 5), 1));
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn oldest_generation_is_forgotten() {
+        let mut table = TranspositionTable::new(2 * 2 * BYTES_PER_ENTRY);
+        let mut mats = Vec::new();
+        for i in 0..3 {
+            let mut mat = Matrix::identity(3, 5);
+            mat.d[1] = Polynomial::new(vec![(0, i + 1)], 5);
+            let mat = DenseMatrix::from(&mat);
+            assert!(table.insert(&mat, 1));
+            mats.push(mat);
+        }
+        // The first two images filled a generation, which was then kept as
+        // the previous one.
+        assert!(!table.insert(&mats[0], 1));
+        let mut mat = Matrix::identity(3, 5);
+        mat.d[1] = Polynomial::new(vec![(0, 4)], 5);
+        assert!(table.insert(&DenseMatrix::from(&mat), 1));
+        assert!(table.insert(&mats[0], 1));
+    }
+}
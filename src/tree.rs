@@ -0,0 +1,149 @@
+//! The search tree, in which every state keeps only its last Garside factor
+//! and a pointer to its parent, so that expanding a state does not copy its
+//! factors and states with a common prefix share it.
+
+use std::fmt;
+use std::sync::Arc;
+
+struct Node {
+    /// Factor ids fit in a `u16`, since B_7 has 5038 factors.
+    factor: u16,
+    depth: u32,
+    parent: Option<Arc<Node>>,
+}
+
+impl Drop for Node {
+    // Dropping a long path recursively could overflow the stack, so the
+    // parents that are no longer shared are dropped one at a time instead.
+    fn drop(&mut self) {
+        let mut parent = self.parent.take();
+        while let Some(node) = parent {
+            parent = match Arc::try_unwrap(node) {
+                Ok(mut node) => node.parent.take(),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+/// The non-empty sequence of factors on the path from the root of the search
+/// tree to a state. Cloning and extending a path take constant time.
+#[derive(Clone)]
+pub struct Path(Arc<Node>);
+
+impl Path {
+    pub fn new(factor: u32) -> Path {
+        Path(Arc::new(Node {
+            factor: to_id(factor),
+            depth: 1,
+            parent: None,
+        }))
+    }
+
+    /// The path with the given factors, which must be non-empty.
+    pub fn from_factors(factors: &[u32]) -> Path {
+        let mut res = Path::new(factors[0]);
+        for factor in &factors[1..] {
+            res = res.push(*factor);
+        }
+        res
+    }
+
+    /// The path extended by `factor`.
+    pub fn push(&self, factor: u32) -> Path {
+        Path(Arc::new(Node {
+            factor: to_id(factor),
+            depth: self.0.depth + 1,
+            parent: Some(self.0.clone()),
+        }))
+    }
+
+    pub fn last(&self) -> u32 {
+        self.0.factor as u32
+    }
+
+    /// The number of factors, which is the depth of the state in the tree.
+    pub fn depth(&self) -> usize {
+        self.0.depth as usize
+    }
+
+    /// The factors from the root, which takes time linear in their number.
+    pub fn to_vec(&self) -> Vec<u32> {
+        let mut res = vec![0; self.depth()];
+        let mut node = Some(&self.0);
+        for x in res.iter_mut().rev() {
+            let this = node.unwrap();
+            *x = this.factor as u32;
+            node = this.parent.as_ref();
+        }
+        res
+    }
+}
+
+fn to_id(factor: u32) -> u16 {
+    u16::try_from(factor).expect("factor ids of the supported braid groups fit in a u16")
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Path) -> bool {
+        let (mut a, mut b) = (&self.0, &other.0);
+        if a.depth != b.depth {
+            return false;
+        }
+        loop {
+            if Arc::ptr_eq(a, b) {
+                return true;
+            }
+            if a.factor != b.factor {
+                return false;
+            }
+            match (&a.parent, &b.parent) {
+                (Some(x), Some(y)) => (a, b) = (x, y),
+                _ => return true,
+            }
+        }
+    }
+}
+
+impl Eq for Path {}
+
+/// Prints the factors like a `Vec<u32>`, as in `[13, 10, 13]`.
+impl fmt::Debug for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_share_their_prefixes() {
+        let root = Path::new(13);
+        let a = root.push(10).push(13);
+        let b = root.push(10).push(4);
+        assert_eq!(a.to_vec(), vec![13, 10, 13]);
+        assert_eq!(b.to_vec(), vec![13, 10, 4]);
+        assert_eq!((a.depth(), a.last()), (3, 13));
+        assert_eq!(a, Path::from_factors(&[13, 10, 13]));
+        assert_ne!(a, b);
+        assert_ne!(a, root.push(10));
+        assert_eq!(format!("{:?}", a), "[13, 10, 13]");
+        assert_eq!(Path::new(5037).last(), 5037);
+    }
+
+    #[test]
+    fn long_paths_are_dropped_without_recursion() {
+        let mut path = Path::new(1);
+        for i in 0..1_000_000 {
+            path = path.push(i % 22 + 1);
+        }
+        let prefix = path.clone();
+        let longer = path.push(3);
+        drop(path);
+        assert_eq!(longer.depth(), 1_000_002);
+        drop(longer);
+        assert_eq!(prefix.depth(), 1_000_001);
+    }
+}
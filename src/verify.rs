@@ -0,0 +1,182 @@
+use std::num::ParseIntError;
+
+use crate::algebra::{Matrix, Polynomial};
+use crate::garside::{act_by, generate_descendants, num_factors, simple_element, ActionTable};
+
+/// Outcome of a single check performed by `verify`.
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, passed: bool, detail: String) -> Check {
+        Check { name, passed, detail }
+    }
+}
+
+/// Parses a factor list as printed by the searches, e.g. `[13, 10, 13, 10]`.
+/// Brackets, commas and whitespace are all accepted as separators.
+pub fn parse_factors(s: &str) -> Result<Vec<u32>, ParseIntError> {
+    s.split(|c: char| c == '[' || c == ']' || c == ',' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.parse())
+        .collect()
+}
+
+/// The reduced Burau matrix of the Artin generator σ_i of B_n mod p, written
+/// out from its definition: the identity except in row `i - 1`, which is
+/// -v, -v^2, -v around the diagonal.
+fn generator_matrix(n: usize, i: usize, p: u32) -> Matrix {
+    let dim = n - 1;
+    let mut res = Matrix::identity(dim, p);
+    let row = i - 1;
+    let minus_v = |power| Polynomial::new(vec![(power, p - 1)], p);
+    if row > 0 {
+        res.d[row * dim + row - 1] = minus_v(1);
+    }
+    res.d[row * dim + row] = minus_v(2);
+    if row + 1 < dim {
+        res.d[row * dim + row + 1] = minus_v(1);
+    }
+    res
+}
+
+/// Checks that `factors` describes a non-trivial braid in B_n whose Burau
+/// image mod p is c·v^k·I. The image is computed as the product of the
+/// matrices of the Artin generators in the positive words of the factors,
+/// independently of the action table used by the searches, and compared
+/// with `act_by`.
+pub fn verify(factors: &[u32], n: usize, p: u32) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let unknown: Vec<u32> = factors
+        .iter()
+        .filter(|x| **x == 0 || **x > num_factors(n))
+        .copied()
+        .collect();
+    checks.push(Check::new(
+        "factor ids",
+        !factors.is_empty() && unknown.is_empty(),
+        if factors.is_empty() {
+            "no factors given".to_string()
+        } else if unknown.is_empty() {
+            format!("{} factors, all in 1..={}", factors.len(), num_factors(n))
+        } else {
+            format!("unknown factors {:?}", unknown)
+        },
+    ));
+    if !checks[0].passed {
+        return checks;
+    }
+
+    let descendants = generate_descendants(n);
+    let violation = factors
+        .windows(2)
+        .position(|w| !descendants[&w[0]].contains(&w[1]));
+    checks.push(Check::new(
+        "left normal form",
+        violation.is_none(),
+        match violation {
+            None => "every factor may follow the previous one".to_string(),
+            Some(i) => format!(
+                "factor {} at position {} may not follow factor {}",
+                factors[i + 1],
+                i + 1,
+                factors[i]
+            ),
+        },
+    ));
+
+    // Every factor is a positive braid other than the identity, and the
+    // exponent sum is a homomorphism to Z, so a positive exponent sum rules
+    // out the trivial braid.
+    let words: Vec<Vec<usize>> = factors.iter().map(|x| simple_element(n, *x).positive_word()).collect();
+    let exponent_sum: usize = words.iter().map(|x| x.len()).sum();
+    checks.push(Check::new(
+        "non-trivial braid",
+        exponent_sum > 0,
+        format!("exponent sum {} in the Artin generators", exponent_sum),
+    ));
+
+    let generators: Vec<Matrix> = (1..n).map(|i| generator_matrix(n, i, p)).collect();
+    let mut image = Matrix::identity(n - 1, p);
+    for letter in words.iter().flatten() {
+        image = &image * &generators[letter - 1];
+    }
+    let projlen = image.projlen();
+    let scalar = image.is_scalar();
+    checks.push(Check::new(
+        "Burau image",
+        scalar,
+        if scalar {
+            format!("product of generator matrices mod {} is c·v^k·I", p)
+        } else {
+            format!(
+                "product of generator matrices mod {} has projlen {} and is not scalar",
+                p, projlen
+            )
+        },
+    ));
+
+    let table = ActionTable::new(n);
+    let mut acted = Matrix::identity(n - 1, p);
+    for factor in factors {
+        acted = act_by(&acted, *factor, &table).unwrap();
+    }
+    checks.push(Check::new(
+        "agrees with act_by",
+        acted == image,
+        if acted == image {
+            "images are equal".to_string()
+        } else {
+            "images differ".to_string()
+        },
+    ));
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_printed_factors() {
+        assert_eq!(parse_factors("[13, 10, 13]").unwrap(), vec![13, 10, 13]);
+        assert_eq!(parse_factors("13 10,13").unwrap(), vec![13, 10, 13]);
+        assert!(parse_factors("13 x").is_err());
+    }
+
+    #[test]
+    fn verify_mod_two_kernel_element() {
+        let factors = parse_factors("[13, 10, 13, 10, 13, 10, 13, 10]").unwrap();
+        let checks = verify(&factors, 4, 2);
+        assert_eq!(checks.len(), 5);
+        assert!(checks.iter().all(|x| x.passed));
+    }
+
+    #[test]
+    fn verify_rejects_non_scalar_image() {
+        let checks = verify(&[13, 10, 13], 4, 3);
+        assert!(checks[..3].iter().all(|x| x.passed));
+        assert!(!checks[3].passed);
+        // The images computed with and without the action table still agree.
+        assert!(checks[4].passed);
+    }
+
+    #[test]
+    fn verify_rejects_non_normal_form() {
+        let checks = verify(&[13, 2, 13], 4, 2);
+        assert!(!checks[1].passed);
+        assert!(checks[2].passed);
+    }
+
+    #[test]
+    fn verify_rejects_unknown_factor() {
+        let checks = verify(&[13, 23], 4, 2);
+        assert_eq!(checks.len(), 1);
+        assert!(!checks[0].passed);
+    }
+}
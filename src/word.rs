@@ -0,0 +1,277 @@
+//! Words in the Artin generators σ1, …, σ_{n-1} and their inverses, for
+//! exchanging braids with other braid software. A word is a sequence of
+//! non-zero letters, where `i` stands for σ_i and `-i` for σ_i⁻¹.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::garside::{left_normal_form, simple_element};
+use crate::permutation::Permutation;
+
+/// How to print a word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    /// Signed generator indices, as in `1 2 -3`.
+    Numbers,
+    /// `s` for generators and `S` for their inverses, as in `s1 s2 S3`.
+    Letters,
+}
+
+impl FromStr for Notation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Notation, String> {
+        match s {
+            "numbers" => Ok(Notation::Numbers),
+            "letters" => Ok(Notation::Letters),
+            _ => Err(format!("unknown notation '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Notation::Numbers => write!(f, "numbers"),
+            Notation::Letters => write!(f, "letters"),
+        }
+    }
+}
+
+/// A letter of a word that is not a generator of B_n or its inverse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidLetter {
+    pub letter: String,
+    pub n: usize,
+}
+
+impl fmt::Display for InvalidLetter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is not a generator of B_{} or its inverse; indices run from 1 to {}",
+            self.letter,
+            self.n,
+            self.n - 1
+        )
+    }
+}
+
+impl std::error::Error for InvalidLetter {}
+
+fn parse_letter(letter: &str, n: usize) -> Option<i32> {
+    let (sign, index) = if let Some(index) = letter.strip_prefix('s') {
+        (1, index)
+    } else if let Some(index) = letter.strip_prefix('S') {
+        (-1, index)
+    } else if let Some(index) = letter.strip_prefix('σ') {
+        match index.strip_suffix("⁻¹").or_else(|| index.strip_suffix("^-1")) {
+            Some(index) => (-1, index),
+            None => (1, index),
+        }
+    } else if let Some(index) = letter.strip_prefix('-') {
+        (-1, index)
+    } else {
+        (1, letter)
+    };
+    let index: i32 = index.parse().ok()?;
+    (1..n as i32).contains(&index).then_some(sign * index)
+}
+
+/// Parses a word in either notation of `format_word`, or with letters
+/// `σ3` and `σ3⁻¹`. Letters may be separated by whitespace, commas, dots or
+/// brackets.
+pub fn parse_word(s: &str, n: usize) -> Result<Vec<i32>, InvalidLetter> {
+    s.split(|c: char| c == '[' || c == ']' || c == ',' || c == '·' || c == '.' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(|x| {
+            parse_letter(x, n).ok_or_else(|| InvalidLetter {
+                letter: x.to_string(),
+                n,
+            })
+        })
+        .collect()
+}
+
+/// Left normal form Δ^k·A1⋯Ar of the braid given by `word`, returned as `k`
+/// and the ids of the Ai. Every σ_i⁻¹ is written as Δ⁻¹ times the simple
+/// element Δ·σ_i⁻¹, and the powers of Δ⁻¹ are collected on the left using
+/// X·Δ⁻¹ = Δ⁻¹·τ(X).
+pub fn normal_form(n: usize, word: &[i32]) -> (i32, Vec<u32>) {
+    let delta = Permutation::half_twist(n);
+    let mut k = 0;
+    let mut simples: Vec<Permutation> = Vec::new();
+    for x in word {
+        let i = x.unsigned_abs() as usize;
+        let mut generator = Permutation::identity(n);
+        generator.images.swap(i - 1, i);
+        if *x > 0 {
+            simples.push(generator);
+        } else {
+            for y in &mut simples {
+                *y = y.flip();
+            }
+            simples.push(&delta * &generator);
+            k -= 1;
+        }
+    }
+    left_normal_form(n, k, &simples)
+}
+
+/// The positive word of a simple element.
+fn positive_word(simple: &Permutation) -> Vec<i32> {
+    simple.positive_word().into_iter().map(|x| x as i32).collect()
+}
+
+/// The word of the inverse of the braid given by `word`.
+pub fn inverse(word: &[i32]) -> Vec<i32> {
+    word.iter().rev().map(|x| -x).collect()
+}
+
+/// The word of Δ^delta_power·A1⋯Ar, given by the ids of the Ai: the word of
+/// Δ or Δ⁻¹ repeated, followed by the positive word of every factor.
+pub fn artin_word(n: usize, delta_power: i32, factors: &[u32]) -> Vec<i32> {
+    let mut delta = positive_word(&Permutation::half_twist(n));
+    if delta_power < 0 {
+        delta = inverse(&delta);
+    }
+    let mut word = delta.repeat(delta_power.unsigned_abs() as usize);
+    for factor in factors {
+        word.extend(positive_word(&simple_element(n, *factor)));
+    }
+    word
+}
+
+/// A word of Δ^delta_power·A1⋯Ar that is usually much shorter than its
+/// `artin_word` when the power of Δ is negative. Every Δ⁻¹ that can be paired
+/// with a factor is absorbed into it, using Δ⁻¹·A = ∂(A)⁻¹ and
+/// Δ⁻¹·X = τ(X)·Δ⁻¹, so that Δ^-m·A1⋯Am becomes the product of the
+/// τ^(m-i)(∂(Ai))⁻¹, each of which is shorter than Δ. The result is freely
+/// reduced.
+pub fn shortened_word(n: usize, delta_power: i32, factors: &[u32]) -> Vec<i32> {
+    let absorbed = if delta_power < 0 {
+        factors.len().min(delta_power.unsigned_abs() as usize)
+    } else {
+        0
+    };
+    let mut word = artin_word(n, delta_power + absorbed as i32, &[]);
+    for (i, factor) in factors[..absorbed].iter().enumerate() {
+        let mut complement = simple_element(n, *factor).complement();
+        if (absorbed - 1 - i) % 2 == 1 {
+            complement = complement.flip();
+        }
+        word.extend(inverse(&positive_word(&complement)));
+    }
+    word.extend(artin_word(n, 0, &factors[absorbed..]));
+    free_reduce(&word)
+}
+
+/// Cancels adjacent pairs of a generator and its inverse until none remain.
+pub fn free_reduce(word: &[i32]) -> Vec<i32> {
+    let mut res: Vec<i32> = Vec::with_capacity(word.len());
+    for x in word {
+        if res.last() == Some(&-x) {
+            res.pop();
+        } else {
+            res.push(*x);
+        }
+    }
+    res
+}
+
+/// Prints a word with its letters separated by spaces.
+pub fn format_word(word: &[i32], notation: Notation) -> String {
+    let letters: Vec<String> = match notation {
+        Notation::Numbers => word.iter().map(|x| x.to_string()).collect(),
+        Notation::Letters => word
+            .iter()
+            .map(|x| format!("{}{}", if *x > 0 { 's' } else { 'S' }, x.unsigned_abs()))
+            .collect(),
+    };
+    letters.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garside::generate_descendants;
+
+    #[test]
+    fn artin_word_concatenates_positive_words() {
+        assert_eq!(artin_word(4, 0, &[13, 10]), vec![2, 3, 1, 3, 1, 2]);
+        assert_eq!(artin_word(4, 1, &[6]), vec![1, 2, 3, 1, 2, 1, 1]);
+        assert_eq!(artin_word(3, -2, &[]), vec![-1, -2, -1, -1, -2, -1]);
+        assert_eq!(normal_form(4, &artin_word(4, -3, &[13, 10, 13])), (-3, vec![13, 10, 13]));
+    }
+
+    #[test]
+    fn free_reduce_cancels_inverse_pairs() {
+        assert_eq!(free_reduce(&[1, 2, -2, -1, 3]), vec![3]);
+        assert_eq!(free_reduce(&[1, -1, 1, 2, 1]), vec![1, 2, 1]);
+        assert_eq!(free_reduce(&[1, 1, -2]), vec![1, 1, -2]);
+        assert_eq!(free_reduce(&[]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn shortened_word_is_same_braid() {
+        // The kernel element of Burau mod 2 found by the collision search,
+        // as well as normal forms with more and fewer factors than Δ⁻¹.
+        let cases = [
+            (-4, vec![10, 13, 10, 13, 10, 13, 10, 13]),
+            (-1, vec![17, 19, 10]),
+            (-5, vec![13, 4]),
+            (2, vec![21]),
+        ];
+        for (k, factors) in cases {
+            let word = shortened_word(4, k, &factors);
+            assert!(word.len() < artin_word(4, k, &factors).len() || k >= 0);
+            assert_eq!(normal_form(4, &word), (k, factors));
+        }
+        for factor in 1..=22 {
+            assert_eq!(normal_form(4, &shortened_word(4, -1, &[factor])), (-1, vec![factor]));
+        }
+    }
+
+    #[test]
+    fn normal_form_solves_word_problem() {
+        let descendants = generate_descendants(4);
+        let words: [&[i32]; 4] = [&[1, 2, -1, 3, -2, -2, 1], &[-3, -3, 2, 1, 1, -2], &[2, 3, 1, -3], &[1, -1]];
+        for word in words {
+            let (k, factors) = normal_form(4, word);
+            assert!(factors.windows(2).all(|w| descendants[&w[0]].contains(&w[1])));
+            assert_eq!(normal_form(4, &artin_word(4, k, &factors)), (k, factors.clone()));
+            let mut trivial = word.to_vec();
+            trivial.extend(inverse(word));
+            trivial.rotate_left(1);
+            assert_eq!(normal_form(4, &trivial), (0, vec![]));
+        }
+        // Relations of B_4.
+        assert_eq!(normal_form(4, &[1, 2, 1]), normal_form(4, &[2, 1, 2]));
+        assert_eq!(normal_form(4, &[1, 3]), normal_form(4, &[3, 1]));
+        assert_eq!(normal_form(4, &[-1]), (-1, vec![17]));
+        assert_ne!(normal_form(4, &[1, 2]), normal_form(4, &[2, 1]));
+    }
+
+    #[test]
+    fn parse_words_in_every_notation() {
+        let expected = vec![1, 2, -3];
+        assert_eq!(parse_word("1 2 -3", 4), Ok(expected.clone()));
+        assert_eq!(parse_word("s1 s2 S3", 4), Ok(expected.clone()));
+        assert_eq!(parse_word("σ1·σ2·σ3⁻¹", 4), Ok(expected.clone()));
+        assert_eq!(parse_word("[1, 2, -3]", 4), Ok(expected));
+        assert_eq!(parse_word("", 4), Ok(vec![]));
+        let error = parse_word("1 4", 4).unwrap_err();
+        assert_eq!(error.letter, "4");
+        assert!(parse_word("s0", 4).is_err());
+        assert!(parse_word("x1", 4).is_err());
+    }
+
+    #[test]
+    fn notations() {
+        let word = [1, 2, -3];
+        assert_eq!(format_word(&word, Notation::Numbers), "1 2 -3");
+        assert_eq!(format_word(&word, Notation::Letters), "s1 s2 S3");
+        assert_eq!("letters".parse(), Ok(Notation::Letters));
+        assert!("greek".parse::<Notation>().is_err());
+    }
+}